@@ -1,12 +1,23 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::arch::apu::Apu;
 use crate::arch::cartridge::Cartridge;
 use crate::arch::cpu::Cpu;
-use crate::arch::mappers::RomFile;
-use crate::arch::ppu::Ppu;
+use crate::arch::mappers::{MapperState, RomFile, RomTiming};
+use crate::arch::ppu::{NesRegion, Ppu};
+use crate::arch::scheduler::{EventKind, Scheduler};
 
+pub mod apu;
+pub mod audio;
 pub mod cartridge;
 pub mod cpu;
+pub mod debug;
+pub mod disasm;
+#[cfg(feature = "gdbstub")]
+pub mod gdb;
 pub mod mappers;
 pub mod ppu;
+pub mod scheduler;
 
 /// Collection of major components found within the NES.
 /// 
@@ -21,26 +32,87 @@ pub mod ppu;
 pub struct Nes {
     pub cpu: Cpu,
     pub ppu: Ppu,
+    pub apu: Apu,
     pub cart: Cartridge,
-    
+
     pub last_bus: BusActivity,
+
+    /// Cycle-timestamped events due to fire at or before the current CPU cycle; drained at the
+    /// end of every [`Nes::tick`]. See [`scheduler`](crate::arch::scheduler) for what's meant to
+    /// eventually move onto this instead of being polled per-cycle.
+    pub scheduler: Scheduler,
 }
 impl Nes {
     pub fn new() -> Self {
         Self::default()
     }
-    
+
+    /// Advance every component by one master-clock cycle, returning `true` if the CPU just hit a
+    /// breakpoint (see [`Cpu::tick`]).
     #[inline(always)]
-    pub fn tick(&mut self) {
-        Cpu::tick(self);
+    pub fn tick(&mut self) -> bool {
+        let hit_breakpoint = Cpu::tick(self);
         Ppu::tick(self);
+
+        if self.cpu.clock_divider.counter == 0 {
+            Apu::tick(self);
+        }
+
+        let cyc = self.cpu.cyc;
+        while let Some(event) = self.scheduler.pop_ready(cyc) {
+            match event.kind {
+                EventKind::Nmi => self.cpu.nmi = false, // set LOW (NMI is active-low)
+                EventKind::CpuResume | EventKind::Irq | EventKind::ApuFrameCounter | EventKind::DmcDma => (),
+            }
+        }
+
+        hit_breakpoint
     }
     
     pub fn load_rom(&mut self, rom: RomFile) {
+        let region = match rom.timing() {
+            RomTiming::Ntsc | RomTiming::MultiRegion => NesRegion::Ntsc,
+            RomTiming::Pal => NesRegion::Pal,
+            RomTiming::Dendy => NesRegion::Dendy,
+        };
+        self.ppu = Ppu::new(region);
+        Cpu::set_region(self, region);
+
         self.cart.mapper = rom.into_mapper();
         Cpu::init_pc(self);
     }
-    
+
+    /// Snapshot the entire machine (CPU, PPU, APU, and cartridge mapper) to a versioned binary
+    /// blob, suitable for writing straight to a save file.
+    pub fn save_state(&self) -> Vec<u8> {
+        let state = NesState {
+            version: NES_STATE_VERSION,
+            cpu: self.cpu.clone(),
+            ppu: self.ppu.clone(),
+            apu: self.apu.clone(),
+            mapper: self.cart.mapper.save_state(),
+            last_bus: self.last_bus,
+        };
+
+        bincode::serialize(&state).expect("NesState encoding is infallible")
+    }
+
+    /// Restore a machine snapshot previously produced by [`Nes::save_state`].
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), NesStateError> {
+        let state: NesState = bincode::deserialize(bytes)?;
+        if state.version != NES_STATE_VERSION {
+            return Err(NesStateError::VersionMismatch { found: state.version, expected: NES_STATE_VERSION });
+        }
+
+        self.cpu = state.cpu;
+        self.ppu = state.ppu;
+        self.apu = state.apu;
+        self.cart.mapper = state.mapper.into_mapper();
+        self.last_bus = state.last_bus;
+
+        Ok(())
+    }
+
     /// Write to the CPU's external bus.
     /// 
     /// This bus is connected to the 2A03 CPU (including the APU and other internal components), PPU, and the cartridge.
@@ -49,11 +121,14 @@ impl Nes {
         self.cpu.predecode = data;
         
         match addr {
-            0x0000..=0x1FFF | 0x4014 => self.cpu.internal_write(addr, data),
+            0x0000..=0x1FFF => self.cpu.write(addr, data),
             0x2000..=0x3FFF => Ppu::port_write(self, addr, data),
-            0x4000..=0x4017 => (),
+            0x4000..=0x4013 | 0x4015 | 0x4017 => Apu::port_write(self, addr, data),
+            0x4014 => Cpu::start_oam_dma(self, data),
+            0x4016 => (), //todo: joypad strobe
+
             0x4018..=0x401F => panic!("Write attempt to CPU Test Mode at address {:#06X} ({:#04X})", addr, data),
-            0x4020..=0xFFFF => self.cart.write_cpu(addr, data),
+            0x4020..=0xFFFF => self.cart.write_cpu(addr, data, self.cpu.cyc),
         }
         
         self.last_bus = BusActivity { addr, data, is_read: false };
@@ -65,9 +140,12 @@ impl Nes {
     #[cfg(not(feature = "sst"))]
     pub fn read(&mut self, addr: u16) -> u8 {
         let data = match addr {
-            0x0000..=0x1FFF => self.cpu.internal_read(addr),
+            0x0000..=0x1FFF => self.cpu.read(addr),
             0x2000..=0x3FFF => Ppu::port_read(self, addr),
-            0x4000..=0x4017 => 0,
+            0x4015 => Apu::port_read(self, addr),
+            // No device drives these lines (todo: $4016/$4017 joypad input; $4014 OAMDMA is
+            // write-only), so the last byte left on the bus lingers and is read back instead.
+            0x4000..=0x4014 | 0x4016..=0x4017 => self.last_bus.data,
             0x4018..=0x401F => panic!("Read attempt to CPU Test Mode at address {:#06X}", addr),
             0x4020..=0xFFFF => self.cart.read_cpu(addr),
         };
@@ -82,16 +160,16 @@ impl Nes {
     #[cfg(feature = "sst")]
     pub fn write(&mut self, addr: u16, data: u8) {
         self.cpu.predecode = data;
-        
-        self.cpu.internal_write(addr, data);
-        
+
+        self.cpu.write(addr, data);
+
         self.last_bus = BusActivity { addr, data, is_read: false };
     }
-    
+
     #[cfg(feature = "sst")]
     pub fn read(&mut self, addr: u16) -> u8 {
-        let data = self.cpu.internal_read(addr);
-        
+        let data = self.cpu.read(addr);
+
         self.cpu.predecode = data;
         
         self.last_bus = BusActivity { addr, data, is_read: true };
@@ -100,28 +178,83 @@ impl Nes {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct BusActivity {
     pub addr: u16,
     pub data: u8,
     pub is_read: bool,
 }
 
+/// The CPU's own internal bus, i.e. just its `$0000-$1FFF` work RAM.
+///
+/// [`Nes::write`]/[`Nes::read`] delegate to this for that address range before falling through to
+/// the PPU/APU/cartridge; [`Cpu`] implements it once per `tomharte`/non-`tomharte` WRAM size.
+pub trait CpuBusAccessible {
+    fn write(&mut self, addr: u16, data: u8);
+    fn read(&mut self, addr: u16) -> u8;
+}
+
+/// On-disk layout version of [`NesState`]. Bump this whenever the struct's fields change in a
+/// way that would corrupt an older save file on decode, so [`Nes::load_state`] can reject it
+/// instead of silently desyncing.
+const NES_STATE_VERSION: u32 = 1;
+
+/// Serializable snapshot of an entire [`Nes`], produced by [`Nes::save_state`].
+///
+/// The cartridge's `Box<dyn Mapper>` can't be derived directly, so it's represented here as a
+/// tagged [`MapperState`] and reconstructed through [`MapperState::into_mapper`] on load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NesState {
+    version: u32,
+    cpu: Cpu,
+    ppu: Ppu,
+    apu: Apu,
+    mapper: MapperState,
+    last_bus: BusActivity,
+}
+
+/// Failure modes when restoring a [`Nes`] from a [`Nes::save_state`] blob.
+#[derive(Debug)]
+pub enum NesStateError {
+    /// The blob isn't a valid encoding of [`NesState`] at all (truncated, corrupt, or from some
+    /// unrelated file).
+    Decode(bincode::Error),
+    /// The blob decoded fine, but was written by a different, incompatible save-state layout.
+    VersionMismatch { found: u32, expected: u32 },
+}
+impl Display for NesStateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NesStateError::Decode(err) => write!(f, "failed to decode save state: {err}"),
+            NesStateError::VersionMismatch { found, expected } =>
+                write!(f, "save state is version {found}, expected {expected}"),
+        }
+    }
+}
+impl std::error::Error for NesStateError {}
+impl From<bincode::Error> for NesStateError {
+    fn from(err: bincode::Error) -> Self {
+        NesStateError::Decode(err)
+    }
+}
+
 
-#[derive(Clone, Debug)]
-pub struct ClockDivider<const N: usize> {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClockDivider {
     pub counter: usize,
+    limit: usize,
 }
-impl<const N: usize> ClockDivider<N> {
-    pub fn new(initial: usize) -> Self { Self {
-        counter: initial
+impl ClockDivider {
+    pub fn new(initial: usize, limit: usize) -> Self { Self {
+        counter: initial,
+        limit,
     }}
-    
+
     pub fn tick(&mut self) -> bool {
         self.counter += 1;
-        if self.counter == N {
+        if self.counter == self.limit {
             self.counter = 0;
-            
+
             true
         } else {
             false
@@ -0,0 +1,728 @@
+use proc_bitfield::bitfield;
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+use crate::arch::{ClockDivider, Nes};
+use crate::arch::audio::AudioWriter;
+
+/// Length counter load values, indexed by the 5-bit field written to each channel's length
+/// counter load register.
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+/// NTSC APU DMC rate table, in CPU cycles per sample bit.
+const DMC_RATE_TABLE: [u16; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+
+/// NTSC APU noise channel period table, in CPU cycles.
+const NOISE_PERIOD_TABLE: [u16; 16] = [
+    4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
+];
+
+const PULSE_DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+const TRIANGLE_SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+bitfield! {
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct DutyVolumeReg(pub u8): Debug {
+        pub volume: u8 @ 0..=3,
+        pub constant_volume: bool @ 4,
+        pub length_counter_halt: bool @ 5,
+        pub duty: u8 @ 6..=7,
+    }
+}
+
+bitfield! {
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SweepReg(pub u8): Debug {
+        pub shift: u8 @ 0..=2,
+        pub negate: bool @ 3,
+        pub period: u8 @ 4..=6,
+        pub enabled: bool @ 7,
+    }
+}
+
+bitfield! {
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct FrameCounterReg(pub u8): Debug {
+        pub irq_inhibit: bool @ 6,
+        /// false = 4-step sequence, true = 5-step sequence
+        pub five_step: bool @ 7,
+    }
+}
+
+/// Envelope generator shared by the pulse and noise channels.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+struct Envelope {
+    start: bool,
+    divider: u8,
+    decay: u8,
+}
+impl Envelope {
+    fn clock(&mut self, loop_flag: bool, volume: u8) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = volume;
+        } else if self.divider == 0 {
+            self.divider = volume;
+
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if loop_flag {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    fn output(&self, constant_volume: bool, volume: u8) -> u8 {
+        if constant_volume { volume } else { self.decay }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pulse {
+    duty_volume: DutyVolumeReg,
+    sweep: SweepReg,
+    timer_period: u16,
+    timer: u16,
+    sequence_pos: u8,
+    length_counter: u8,
+    sweep_reload: bool,
+    sweep_divider: u8,
+    envelope: Envelope,
+    /// Distinguishes pulse 1 (one's-complement sweep subtraction) from pulse 2 (two's-complement)
+    is_pulse2: bool,
+}
+impl Default for Pulse {
+    fn default() -> Self { Self {
+        duty_volume: DutyVolumeReg(0),
+        sweep: SweepReg(0),
+        timer_period: 0,
+        timer: 0,
+        sequence_pos: 0,
+        length_counter: 0,
+        sweep_reload: false,
+        sweep_divider: 0,
+        envelope: Envelope::default(),
+        is_pulse2: false,
+    }}
+}
+impl Pulse {
+    fn sweep_target(&self) -> u16 {
+        let change = self.timer_period >> self.sweep.shift();
+
+        if self.sweep.negate() {
+            if self.is_pulse2 {
+                self.timer_period.wrapping_sub(change)
+            } else {
+                self.timer_period.wrapping_sub(change).wrapping_sub(1)
+            }
+        } else {
+            self.timer_period.wrapping_add(change)
+        }
+    }
+
+    fn sweep_muted(&self) -> bool {
+        self.timer_period < 8 || self.sweep_target() > 0x7FF
+    }
+
+    fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence_pos = (self.sequence_pos + 1) % 8;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn clock_envelope(&mut self) {
+        self.envelope.clock(self.duty_volume.length_counter_halt(), self.duty_volume.volume());
+    }
+
+    fn clock_sweep(&mut self) {
+        if self.sweep_divider == 0 && self.sweep.enabled() && !self.sweep_muted() {
+            self.timer_period = self.sweep_target();
+        }
+
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep.period();
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+
+    fn clock_length_counter(&mut self) {
+        if self.length_counter > 0 && !self.duty_volume.length_counter_halt() {
+            self.length_counter -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.length_counter == 0 || self.sweep_muted() || PULSE_DUTY_TABLE[self.duty_volume.duty() as usize][self.sequence_pos as usize] == 0 {
+            0
+        } else {
+            self.envelope.output(self.duty_volume.constant_volume(), self.duty_volume.volume())
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Triangle {
+    control_flag: bool,
+    linear_counter_reload: u8,
+    linear_counter: u8,
+    linear_counter_reload_flag: bool,
+    timer_period: u16,
+    timer: u16,
+    sequence_pos: u8,
+    length_counter: u8,
+}
+impl Triangle {
+    fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+
+            if self.linear_counter > 0 && self.length_counter > 0 {
+                self.sequence_pos = (self.sequence_pos + 1) % 32;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn clock_linear_counter(&mut self) {
+        if self.linear_counter_reload_flag {
+            self.linear_counter = self.linear_counter_reload;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control_flag {
+            self.linear_counter_reload_flag = false;
+        }
+    }
+
+    fn clock_length_counter(&mut self) {
+        if self.length_counter > 0 && !self.control_flag {
+            self.length_counter -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        // Ultrasonic periods (period < 2) are silenced rather than producing a pop, matching
+        // the real APU's behavior of simply halting the sequencer.
+        if self.length_counter == 0 || self.linear_counter == 0 || self.timer_period < 2 {
+            0
+        } else {
+            TRIANGLE_SEQUENCE[self.sequence_pos as usize]
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Noise {
+    duty_volume: DutyVolumeReg,
+    mode: bool,
+    period_index: u8,
+    timer: u16,
+    shift_reg: u16,
+    length_counter: u8,
+    envelope: Envelope,
+}
+impl Default for Noise {
+    fn default() -> Self { Self {
+        duty_volume: DutyVolumeReg(0),
+        mode: false,
+        period_index: 0,
+        timer: 0,
+        // The LFSR powers on loaded with 1; an all-zero state would never produce noise.
+        shift_reg: 1,
+        length_counter: 0,
+        envelope: Envelope::default(),
+    }}
+}
+impl Noise {
+    fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = NOISE_PERIOD_TABLE[self.period_index as usize] / 2;
+
+            let bit = if self.mode {
+                (self.shift_reg & 0x01) ^ ((self.shift_reg >> 6) & 0x01)
+            } else {
+                (self.shift_reg & 0x01) ^ ((self.shift_reg >> 1) & 0x01)
+            };
+
+            self.shift_reg >>= 1;
+            self.shift_reg |= bit << 14;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn clock_envelope(&mut self) {
+        self.envelope.clock(self.duty_volume.length_counter_halt(), self.duty_volume.volume());
+    }
+
+    fn clock_length_counter(&mut self) {
+        if self.length_counter > 0 && !self.duty_volume.length_counter_halt() {
+            self.length_counter -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.length_counter == 0 || self.shift_reg & 0x01 != 0 {
+            0
+        } else {
+            self.envelope.output(self.duty_volume.constant_volume(), self.duty_volume.volume())
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Dmc {
+    irq_enabled: bool,
+    loop_flag: bool,
+    rate_index: u8,
+    timer: u16,
+    output_level: u8,
+    sample_addr: u16,
+    sample_length: u16,
+    current_addr: u16,
+    bytes_remaining: u16,
+    sample_buffer: Option<u8>,
+    shift_reg: u8,
+    bits_remaining: u8,
+    silence: bool,
+    pub irq_flag: bool,
+}
+impl Dmc {
+    fn restart(&mut self) {
+        self.current_addr = self.sample_addr;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    fn fetch_sample(nes: &mut Nes) {
+        if nes.apu.dmc.sample_buffer.is_none() && nes.apu.dmc.bytes_remaining > 0 {
+            //TODO: this read should steal CPU cycles, similar to OAMDMA
+            let addr = nes.apu.dmc.current_addr;
+            let data = nes.read(addr);
+
+            nes.apu.dmc.sample_buffer = Some(data);
+            nes.apu.dmc.current_addr = if nes.apu.dmc.current_addr == 0xFFFF { 0x8000 } else { nes.apu.dmc.current_addr + 1 };
+            nes.apu.dmc.bytes_remaining -= 1;
+
+            if nes.apu.dmc.bytes_remaining == 0 {
+                if nes.apu.dmc.loop_flag {
+                    nes.apu.dmc.restart();
+                } else if nes.apu.dmc.irq_enabled {
+                    nes.apu.dmc.irq_flag = true;
+                }
+            }
+        }
+    }
+
+    fn clock_timer(nes: &mut Nes) {
+        if nes.apu.dmc.timer == 0 {
+            nes.apu.dmc.timer = DMC_RATE_TABLE[nes.apu.dmc.rate_index as usize] / 2;
+
+            if !nes.apu.dmc.silence {
+                if nes.apu.dmc.shift_reg & 0x01 != 0 {
+                    if nes.apu.dmc.output_level <= 125 {
+                        nes.apu.dmc.output_level += 2;
+                    }
+                } else if nes.apu.dmc.output_level >= 2 {
+                    nes.apu.dmc.output_level -= 2;
+                }
+            }
+
+            nes.apu.dmc.shift_reg >>= 1;
+            nes.apu.dmc.bits_remaining = nes.apu.dmc.bits_remaining.saturating_sub(1);
+
+            if nes.apu.dmc.bits_remaining == 0 {
+                nes.apu.dmc.bits_remaining = 8;
+
+                match nes.apu.dmc.sample_buffer.take() {
+                    Some(data) => {
+                        nes.apu.dmc.silence = false;
+                        nes.apu.dmc.shift_reg = data;
+                    },
+                    None => nes.apu.dmc.silence = true,
+                }
+
+                Dmc::fetch_sample(nes);
+            }
+        } else {
+            nes.apu.dmc.timer -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        self.output_level
+    }
+}
+
+/// Frame sequencer driving the quarter-frame (envelope/linear counter) and half-frame (length
+/// counter/sweep) clocks shared by all five channels.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FrameCounter {
+    reg: FrameCounterReg,
+    /// CPU cycle divider; the frame sequencer runs at half the CPU clock.
+    divider: ClockDivider,
+    step: u8,
+    /// Delays mode-register writes' immediate clock by the same number of CPU cycles real
+    /// hardware takes to apply them (3 or 4, depending on write alignment).
+    reset_delay: u8,
+    pub irq_flag: bool,
+}
+impl Default for FrameCounter {
+    fn default() -> Self { Self {
+        reg: FrameCounterReg(0),
+        divider: ClockDivider::new(0, 2),
+        step: 0,
+        reset_delay: 0,
+        irq_flag: false,
+    }}
+}
+
+/// The 2A03 Audio Processing Unit: five channels (two pulse, triangle, noise, DMC), their shared
+/// frame sequencer, and a simple sample-rate-converted output callback.
+///
+/// `Clone` and `Debug` are hand-written rather than derived, since `sample_callback` is a
+/// `Box<dyn FnMut>` and implements neither; see the impls below.
+#[derive(Serialize, Deserialize)]
+pub struct Apu {
+    pub pulse1: Pulse,
+    pub pulse2: Pulse,
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: Dmc,
+    frame_counter: FrameCounter,
+
+    /// Accumulates CPU cycles between emitted samples, since the CPU runs faster than typical
+    /// output sample rates (e.g. 1.789773 MHz NTSC vs. 44.1 kHz audio).
+    sample_divider: f64,
+    sample_period: f64,
+
+    /// Invoked with a mixed, normalized (`-1.0..=1.0`) sample each time one is ready.
+    ///
+    /// Not part of the machine's architectural state, so it's dropped by save states; re-attach
+    /// it with [`Apu::set_sample_callback`] after loading.
+    #[allow(clippy::type_complexity)]
+    #[serde(skip)]
+    pub sample_callback: Option<Box<dyn FnMut(f32) + Send>>,
+
+    /// Lock-free alternative to `sample_callback`: mixed samples are pushed into this ring
+    /// buffer's writer half instead of (or alongside) invoking a callback, so a separate audio
+    /// thread can drain them via [`AudioReader`](crate::arch::audio::AudioReader) without
+    /// blocking the emulation thread. Attach one with [`Apu::set_audio_writer`].
+    ///
+    /// Not part of the machine's architectural state, so it's dropped by save states, same as
+    /// `sample_callback`.
+    #[serde(skip)]
+    pub audio_writer: Option<AudioWriter>,
+}
+impl Clone for Apu {
+    /// `sample_callback` is dropped rather than cloned, same as it is on save/load; re-attach it
+    /// with [`Apu::set_sample_callback`] if the clone needs one.
+    fn clone(&self) -> Self {
+        Self {
+            pulse1: self.pulse1.clone(),
+            pulse2: self.pulse2.clone(),
+            triangle: self.triangle.clone(),
+            noise: self.noise.clone(),
+            dmc: self.dmc.clone(),
+            frame_counter: self.frame_counter.clone(),
+            sample_divider: self.sample_divider,
+            sample_period: self.sample_period,
+            sample_callback: None,
+            audio_writer: self.audio_writer.clone(),
+        }
+    }
+}
+impl std::fmt::Debug for Apu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Apu")
+            .field("pulse1", &self.pulse1)
+            .field("pulse2", &self.pulse2)
+            .field("triangle", &self.triangle)
+            .field("noise", &self.noise)
+            .field("dmc", &self.dmc)
+            .field("frame_counter", &self.frame_counter)
+            .field("sample_divider", &self.sample_divider)
+            .field("sample_period", &self.sample_period)
+            .field("audio_writer", &self.audio_writer)
+            .finish_non_exhaustive()
+    }
+}
+impl Default for Apu {
+    fn default() -> Self { Self {
+        pulse1: Pulse::default(),
+        pulse2: Pulse { is_pulse2: true, ..Pulse::default() },
+        triangle: Triangle::default(),
+        noise: Noise::default(),
+        dmc: Dmc::default(),
+        frame_counter: FrameCounter::default(),
+
+        sample_divider: 0.0,
+        sample_period: 0.0,
+
+        sample_callback: None,
+        audio_writer: None,
+    }}
+}
+impl Apu {
+    /// Configure the callback invoked once per output sample, and the CPU-cycle rate (e.g.
+    /// `1_789_773.0 / 44100.0` for NTSC at 44.1 kHz) at which samples are produced.
+    pub fn set_sample_callback(&mut self, sample_period: f64, callback: Box<dyn FnMut(f32) + Send>) {
+        self.sample_period = sample_period;
+        self.sample_callback = Some(callback);
+    }
+
+    /// Configure the sample-rate-conversion period (see [`Apu::set_sample_callback`]) and attach
+    /// a lock-free ring buffer writer, so samples can be drained from a separate audio thread via
+    /// its [`AudioReader`](crate::arch::audio::AudioReader) instead of through a callback.
+    pub fn set_audio_writer(&mut self, sample_period: f64, writer: AudioWriter) {
+        self.sample_period = sample_period;
+        self.audio_writer = Some(writer);
+    }
+
+    #[inline(always)]
+    pub fn tick(nes: &mut Nes) {
+        Apu::clock_frame_counter(nes);
+
+        // Pulse, noise, and DMC timers tick once per APU cycle (every other CPU cycle).
+        if nes.cpu.cyc % 2 == 0 {
+            nes.apu.pulse1.clock_timer();
+            nes.apu.pulse2.clock_timer();
+            nes.apu.noise.clock_timer();
+            Dmc::clock_timer(nes);
+        }
+
+        // The triangle's timer is clocked at the full CPU rate, unlike the other channels.
+        nes.apu.triangle.clock_timer();
+
+        Apu::emit_sample(nes);
+    }
+
+    fn clock_frame_counter(nes: &mut Nes) {
+        let divider_ticked = nes.apu.frame_counter.divider.tick();
+
+        if nes.apu.frame_counter.reset_delay > 0 {
+            nes.apu.frame_counter.reset_delay -= 1;
+
+            if nes.apu.frame_counter.reset_delay == 0 {
+                nes.apu.frame_counter.step = 0;
+                Apu::clock_quarter_frame(nes);
+                Apu::clock_half_frame(nes);
+
+                return;
+            }
+        }
+
+        if !divider_ticked {
+            return;
+        }
+
+        nes.apu.frame_counter.step += 1;
+
+        let five_step = nes.apu.frame_counter.reg.five_step();
+        let last_step = if five_step { 5 } else { 4 };
+
+        Apu::clock_quarter_frame(nes);
+
+        if nes.apu.frame_counter.step == 2 || nes.apu.frame_counter.step == last_step {
+            Apu::clock_half_frame(nes);
+        }
+
+        if !five_step && nes.apu.frame_counter.step == 4 && !nes.apu.frame_counter.reg.irq_inhibit() {
+            nes.apu.frame_counter.irq_flag = true;
+        }
+
+        if nes.apu.frame_counter.step == last_step {
+            nes.apu.frame_counter.step = 0;
+        }
+    }
+
+    fn clock_quarter_frame(nes: &mut Nes) {
+        nes.apu.pulse1.clock_envelope();
+        nes.apu.pulse2.clock_envelope();
+        nes.apu.noise.clock_envelope();
+        nes.apu.triangle.clock_linear_counter();
+    }
+
+    fn clock_half_frame(nes: &mut Nes) {
+        nes.apu.pulse1.clock_length_counter();
+        nes.apu.pulse2.clock_length_counter();
+        nes.apu.noise.clock_length_counter();
+        nes.apu.triangle.clock_length_counter();
+
+        nes.apu.pulse1.clock_sweep();
+        nes.apu.pulse2.clock_sweep();
+    }
+
+    fn mix(nes: &Nes) -> f32 {
+        let pulse1 = nes.apu.pulse1.output() as f32;
+        let pulse2 = nes.apu.pulse2.output() as f32;
+        let triangle = nes.apu.triangle.output() as f32;
+        let noise = nes.apu.noise.output() as f32;
+        let dmc = nes.apu.dmc.output() as f32;
+
+        // Standard non-linear additive mixing formulas, as documented on the NESdev wiki.
+        let pulse_out = if pulse1 + pulse2 == 0.0 { 0.0 } else { 95.88 / ((8128.0 / (pulse1 + pulse2)) + 100.0) };
+        let tnd_out = if triangle + noise + dmc == 0.0 {
+            0.0
+        } else {
+            159.79 / ((1.0 / (triangle / 8227.0 + noise / 12241.0 + dmc / 22638.0)) + 100.0)
+        };
+
+        (pulse_out + tnd_out) * 2.0 - 1.0
+    }
+
+    fn emit_sample(nes: &mut Nes) {
+        let has_sink = nes.apu.sample_callback.is_some() || nes.apu.audio_writer.is_some();
+        if !has_sink || nes.apu.sample_period <= 0.0 {
+            return;
+        }
+
+        nes.apu.sample_divider += 1.0;
+
+        if nes.apu.sample_divider >= nes.apu.sample_period {
+            nes.apu.sample_divider -= nes.apu.sample_period;
+
+            let sample = Apu::mix(nes);
+            if let Some(callback) = &mut nes.apu.sample_callback {
+                callback(sample);
+            }
+            if let Some(writer) = &nes.apu.audio_writer {
+                writer.push(sample);
+            }
+        }
+    }
+
+    pub fn port_read(nes: &mut Nes, addr: u16) -> u8 {
+        match addr {
+            0x4015 => {
+                let data = ((nes.apu.dmc.irq_flag as u8) << 7)
+                    | ((nes.apu.frame_counter.irq_flag as u8) << 6)
+                    | (nes.last_bus.data & 0b0010_0000) // bit 5 is unconnected; open bus shows through
+                    | (((nes.apu.dmc.bytes_remaining > 0) as u8) << 4)
+                    | (((nes.apu.noise.length_counter > 0) as u8) << 3)
+                    | (((nes.apu.triangle.length_counter > 0) as u8) << 2)
+                    | (((nes.apu.pulse2.length_counter > 0) as u8) << 1)
+                    | ((nes.apu.pulse1.length_counter > 0) as u8);
+
+                nes.apu.frame_counter.irq_flag = false;
+
+                data
+            },
+            _ => nes.last_bus.data, // only reachable for $4015; open bus for anything else
+        }
+    }
+
+    pub fn port_write(nes: &mut Nes, addr: u16, data: u8) {
+        trace!("APU write {data:#04X} to {addr:#06X}");
+
+        match addr {
+            0x4000 => nes.apu.pulse1.duty_volume = DutyVolumeReg(data),
+            0x4001 => { nes.apu.pulse1.sweep = SweepReg(data); nes.apu.pulse1.sweep_reload = true; },
+            0x4002 => nes.apu.pulse1.timer_period = (nes.apu.pulse1.timer_period & 0x700) | data as u16,
+            0x4003 => {
+                nes.apu.pulse1.timer_period = (nes.apu.pulse1.timer_period & 0xFF) | (((data & 0x07) as u16) << 8);
+                nes.apu.pulse1.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                nes.apu.pulse1.sequence_pos = 0;
+                nes.apu.pulse1.envelope.start = true;
+            },
+
+            0x4004 => nes.apu.pulse2.duty_volume = DutyVolumeReg(data),
+            0x4005 => { nes.apu.pulse2.sweep = SweepReg(data); nes.apu.pulse2.sweep_reload = true; },
+            0x4006 => nes.apu.pulse2.timer_period = (nes.apu.pulse2.timer_period & 0x700) | data as u16,
+            0x4007 => {
+                nes.apu.pulse2.timer_period = (nes.apu.pulse2.timer_period & 0xFF) | (((data & 0x07) as u16) << 8);
+                nes.apu.pulse2.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                nes.apu.pulse2.sequence_pos = 0;
+                nes.apu.pulse2.envelope.start = true;
+            },
+
+            0x4008 => {
+                nes.apu.triangle.control_flag = data & 0x80 != 0;
+                nes.apu.triangle.linear_counter_reload = data & 0x7F;
+            },
+            0x4009 => (),
+            0x400A => nes.apu.triangle.timer_period = (nes.apu.triangle.timer_period & 0x700) | data as u16,
+            0x400B => {
+                nes.apu.triangle.timer_period = (nes.apu.triangle.timer_period & 0xFF) | (((data & 0x07) as u16) << 8);
+                nes.apu.triangle.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                nes.apu.triangle.linear_counter_reload_flag = true;
+            },
+
+            0x400C => nes.apu.noise.duty_volume = DutyVolumeReg(data),
+            0x400D => (),
+            0x400E => {
+                nes.apu.noise.mode = data & 0x80 != 0;
+                nes.apu.noise.period_index = data & 0x0F;
+            },
+            0x400F => {
+                nes.apu.noise.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                nes.apu.noise.envelope.start = true;
+            },
+
+            0x4010 => {
+                nes.apu.dmc.irq_enabled = data & 0x80 != 0;
+                nes.apu.dmc.loop_flag = data & 0x40 != 0;
+                nes.apu.dmc.rate_index = data & 0x0F;
+
+                if !nes.apu.dmc.irq_enabled {
+                    nes.apu.dmc.irq_flag = false;
+                }
+            },
+            0x4011 => nes.apu.dmc.output_level = data & 0x7F,
+            0x4012 => nes.apu.dmc.sample_addr = 0xC000 | ((data as u16) << 6),
+            0x4013 => nes.apu.dmc.sample_length = ((data as u16) << 4) | 1,
+
+            0x4015 => {
+                nes.apu.pulse1.length_counter = if data & 0x01 != 0 { nes.apu.pulse1.length_counter } else { 0 };
+                if data & 0x01 == 0 { nes.apu.pulse1.length_counter = 0; }
+                nes.apu.pulse2.length_counter = if data & 0x02 != 0 { nes.apu.pulse2.length_counter } else { 0 };
+                nes.apu.triangle.length_counter = if data & 0x04 != 0 { nes.apu.triangle.length_counter } else { 0 };
+                nes.apu.noise.length_counter = if data & 0x08 != 0 { nes.apu.noise.length_counter } else { 0 };
+
+                nes.apu.dmc.irq_flag = false;
+                if data & 0x10 != 0 {
+                    if nes.apu.dmc.bytes_remaining == 0 {
+                        nes.apu.dmc.restart();
+                    }
+                } else {
+                    nes.apu.dmc.bytes_remaining = 0;
+                }
+            },
+
+            0x4017 => {
+                nes.apu.frame_counter.reg = FrameCounterReg(data);
+                // Writing resets the sequencer after a 3 or 4 cycle delay, depending on whether
+                // the write lands on an even or odd CPU cycle.
+                nes.apu.frame_counter.reset_delay = if nes.cpu.cyc % 2 == 0 { 3 } else { 4 };
+
+                if nes.apu.frame_counter.reg.irq_inhibit() {
+                    nes.apu.frame_counter.irq_flag = false;
+                }
+            },
+
+            _ => (),
+        }
+    }
+}
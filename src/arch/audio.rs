@@ -0,0 +1,100 @@
+//! Lock-free single-producer/single-consumer ring buffer for mixed audio samples.
+//!
+//! [`Apu::emit_sample`](crate::arch::apu::Apu) runs on the emulation thread and can't afford to
+//! block on a mutex every time it has a sample ready, so [`audio_ring_buffer`] hands out a
+//! [`AudioWriter`]/[`AudioReader`] pair sharing one fixed-size backing array, synchronized purely
+//! through atomic read/write cursors. The writer drops samples on overrun rather than blocking
+//! (see [`AudioWriter::push`]); the reader's job is to drain it from a separate audio callback
+//! thread, resampling as needed to the host device's rate.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+struct RingBuffer {
+    samples: Box<[AtomicU32]>,
+    read: AtomicUsize,
+    write: AtomicUsize,
+}
+impl RingBuffer {
+    fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+/// Producer half of an [`audio_ring_buffer`] pair. Cheap to [`Clone`] (shares the same backing
+/// buffer), but only ever meant to be driven from one thread at a time.
+#[derive(Clone)]
+pub struct AudioWriter(Arc<RingBuffer>);
+impl AudioWriter {
+    /// Push a mixed, normalized (`-1.0..=1.0`) sample. Silently drops the sample if the buffer is
+    /// full, since the producer (the emulator) must never stall waiting on the consumer.
+    pub fn push(&self, sample: f32) {
+        let read = self.0.read.load(Ordering::Acquire);
+        let write = self.0.write.load(Ordering::Relaxed);
+        let next = (write + 1) % self.0.capacity();
+
+        if next == read {
+            return;
+        }
+
+        self.0.samples[write].store(sample.to_bits(), Ordering::Relaxed);
+        self.0.write.store(next, Ordering::Release);
+    }
+
+    pub fn is_full(&self) -> bool {
+        let read = self.0.read.load(Ordering::Acquire);
+        let write = self.0.write.load(Ordering::Relaxed);
+
+        (write + 1) % self.0.capacity() == read
+    }
+}
+impl std::fmt::Debug for AudioWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioWriter").finish_non_exhaustive()
+    }
+}
+
+/// Consumer half of an [`audio_ring_buffer`] pair, meant to be drained from an audio callback
+/// thread at the host device's own rate.
+pub struct AudioReader(Arc<RingBuffer>);
+impl AudioReader {
+    /// Pop the oldest sample, or `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<f32> {
+        let write = self.0.write.load(Ordering::Acquire);
+        let read = self.0.read.load(Ordering::Relaxed);
+
+        if read == write {
+            return None;
+        }
+
+        let sample = f32::from_bits(self.0.samples[read].load(Ordering::Relaxed));
+        self.0.read.store((read + 1) % self.0.capacity(), Ordering::Release);
+
+        Some(sample)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let write = self.0.write.load(Ordering::Acquire);
+        let read = self.0.read.load(Ordering::Relaxed);
+
+        read == write
+    }
+}
+impl std::fmt::Debug for AudioReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioReader").finish_non_exhaustive()
+    }
+}
+
+/// Build a fresh ring buffer holding up to `capacity - 1` samples (one slot is kept empty to
+/// distinguish "full" from "empty" without a separate length counter), returning its
+/// writer/reader halves.
+pub fn audio_ring_buffer(capacity: usize) -> (AudioWriter, AudioReader) {
+    let buffer = Arc::new(RingBuffer {
+        samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+        read: AtomicUsize::new(0),
+        write: AtomicUsize::new(0),
+    });
+
+    (AudioWriter(buffer.clone()), AudioReader(buffer))
+}
@@ -0,0 +1,148 @@
+//! Interactive stepping debugger: a small command REPL for pausing a running [`Nes`], inspecting
+//! its CPU bus, and setting breakpoints.
+
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use crate::arch::cpu::Cpu;
+use crate::arch::Nes;
+
+#[derive(Debug)]
+pub enum DebugError {
+    UnknownCommand(String),
+    MissingArg(&'static str),
+    InvalidArg(String),
+}
+impl Display for DebugError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DebugError::UnknownCommand(cmd) => write!(f, "unknown command: {cmd}"),
+            DebugError::MissingArg(usage) => write!(f, "missing argument, usage: {usage}"),
+            DebugError::InvalidArg(arg) => write!(f, "invalid argument: {arg}"),
+        }
+    }
+}
+impl std::error::Error for DebugError {}
+
+pub type Result<T> = std::result::Result<T, DebugError>;
+
+fn parse_addr(s: &str) -> Result<u16> {
+    let s = s.trim_start_matches("0x").trim_start_matches('$');
+    u16::from_str_radix(s, 16).map_err(|_| DebugError::InvalidArg(s.to_string()))
+}
+
+/// Command-line stepping debugger, driven via [`Debugger::run`] in place of the free-running
+/// main loop.
+#[derive(Debug, Default)]
+pub struct Debugger {
+    last_command: Option<String>,
+    repeat: u32,
+    /// When set, `step`/`continue` print each completed instruction's register state instead of
+    /// just silently executing it.
+    trace_only: bool,
+}
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read commands from stdin and dispatch them until the user quits.
+    pub fn run(&mut self, nes: &mut Nes) {
+        loop {
+            print!("(debug) ");
+            let _ = std::io::stdout().flush();
+
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                break; // EOF
+            }
+
+            let args: Vec<&str> = line.trim().split_whitespace().collect();
+            match self.run_debugger_command(nes, &args) {
+                Ok(true) => break,
+                Ok(false) => (),
+                Err(e) => println!("error: {e}"),
+            }
+        }
+    }
+
+    /// Dispatch one REPL command. Returns `Ok(true)` if the REPL should exit.
+    ///
+    /// An empty `args` repeats the last non-empty command line verbatim, including whatever
+    /// repeat count it carried as a trailing numeric argument.
+    pub fn run_debugger_command(&mut self, nes: &mut Nes, args: &[&str]) -> Result<bool> {
+        let owned;
+        let args = if args.is_empty() {
+            match &self.last_command {
+                Some(last) => {
+                    owned = last.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+                    owned.iter().map(String::as_str).collect::<Vec<_>>()
+                },
+                None => return Ok(false),
+            }
+        } else {
+            self.last_command = Some(args.join(" "));
+            args.to_vec()
+        };
+
+        self.repeat = args.last()
+            .and_then(|a| a.parse::<u32>().ok())
+            .unwrap_or(1)
+            .max(1);
+
+        let Some(&command) = args.first() else { return Ok(false); };
+
+        match command {
+            "step" | "s" => {
+                for _ in 0..self.repeat {
+                    Cpu::step_instruction(nes);
+                    if self.trace_only {
+                        self.print_regs(nes);
+                    }
+                }
+            },
+            "continue" | "c" => {
+                while !nes.tick() {
+                    // `proc.done` stays set until the next CPU cycle, so only check right after one.
+                    if self.trace_only && nes.cpu.proc.done && nes.cpu.clock_divider.counter == 0 {
+                        self.print_regs(nes);
+                    }
+                }
+            },
+            "break" => match args.get(1).copied() {
+                Some("clear") => nes.cpu.breakpoints.clear(),
+                Some(addr) => { nes.cpu.breakpoints.insert(parse_addr(addr)?); },
+                None => return Err(DebugError::MissingArg("break <addr> | break clear")),
+            },
+            "read" => {
+                let addr = parse_addr(args.get(1).ok_or(DebugError::MissingArg("read <addr> [len]"))?)?;
+                let len = args.get(2).and_then(|l| l.parse::<u16>().ok()).unwrap_or(1);
+
+                for offset in 0..len {
+                    let addr = addr.wrapping_add(offset);
+                    println!("{:#06X}: {:#04X}", addr, nes.read(addr));
+                }
+            },
+            "write" => {
+                let addr = parse_addr(args.get(1).ok_or(DebugError::MissingArg("write <addr> <val>"))?)?;
+                let data = args.get(2).ok_or(DebugError::MissingArg("write <addr> <val>"))?;
+                let data = u8::from_str_radix(data.trim_start_matches("0x"), 16)
+                    .map_err(|_| DebugError::InvalidArg(data.to_string()))?;
+
+                nes.write(addr, data);
+            },
+            "regs" => self.print_regs(nes),
+            "trace" => self.trace_only = !self.trace_only,
+            "quit" | "q" => return Ok(true),
+            _ => return Err(DebugError::UnknownCommand(command.to_string())),
+        }
+
+        Ok(false)
+    }
+
+    fn print_regs(&self, nes: &Nes) {
+        println!(
+            "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} SP:{:02X} P:{:02X} CYC:{}",
+            nes.cpu.pc, nes.cpu.acc, nes.cpu.x, nes.cpu.y, nes.cpu.sp.0, nes.cpu.status.bits(), nes.cpu.cyc,
+        );
+    }
+}
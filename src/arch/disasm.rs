@@ -0,0 +1,352 @@
+//! Static disassembly of 6502 instruction bytes, used by the `nestest`-style trace emitter (see
+//! `--trace` in `main.rs`) to turn a raw opcode stream into a human- and diff-readable mnemonic
+//! line without re-deriving addressing-mode behavior from [`Cpu::cycle`]'s dispatch table.
+
+use crate::arch::cpu::{AddrMode, TestState};
+use crate::arch::Nes;
+use AddrMode::*;
+
+/// Mnemonic and true addressing mode for every opcode, keyed by byte value.
+///
+/// This mirrors [`Cpu::cycle`]'s decode table, except a handful of opcodes there are tagged
+/// `Auto` (their addressing is handled ad hoc inside the instruction function rather than via
+/// [`effective_addr`]/[`read_modify_write`]); those are given their real mode here so operand
+/// formatting and instruction length come out correct. Unofficial JAM opcodes are listed as
+/// implied with no operand, the same as in the dispatch table.
+fn decode_info(opcode: u8) -> (&'static str, AddrMode) {
+    match opcode {
+        0x00 => ("BRK", Implied),
+        0x01 => ("ORA", IndirectX),
+        0x02 => ("JAM", Implied),
+        0x03 => ("SLO", IndirectX),
+        0x04 => ("NOP", Zero),
+        0x05 => ("ORA", Zero),
+        0x06 => ("ASL", Zero),
+        0x07 => ("SLO", Zero),
+        0x08 => ("PHP", Implied),
+        0x09 => ("ORA", Immediate),
+        0x0A => ("ASL", Accumulator),
+        0x0B => ("ANC", Immediate),
+        0x0C => ("NOP", Absolute),
+        0x0D => ("ORA", Absolute),
+        0x0E => ("ASL", Absolute),
+        0x0F => ("SLO", Absolute),
+        0x10 => ("BPL", Relative),
+        0x11 => ("ORA", IndirectY),
+        0x12 => ("JAM", Implied),
+        0x13 => ("SLO", IndirectY),
+        0x14 => ("NOP", ZeroX),
+        0x15 => ("ORA", ZeroX),
+        0x16 => ("ASL", ZeroX),
+        0x17 => ("SLO", ZeroX),
+        0x18 => ("CLC", Implied),
+        0x19 => ("ORA", AbsoluteY),
+        0x1A => ("NOP", Implied),
+        0x1B => ("SLO", AbsoluteY),
+        0x1C => ("NOP", AbsoluteX),
+        0x1D => ("ORA", AbsoluteX),
+        0x1E => ("ASL", AbsoluteX),
+        0x1F => ("SLO", AbsoluteX),
+        0x20 => ("JSR", Absolute),
+        0x21 => ("AND", IndirectX),
+        0x22 => ("JAM", Implied),
+        0x23 => ("RLA", IndirectX),
+        0x24 => ("BIT", Zero),
+        0x25 => ("AND", Zero),
+        0x26 => ("ROL", Zero),
+        0x27 => ("RLA", Zero),
+        0x28 => ("PLP", Implied),
+        0x29 => ("AND", Immediate),
+        0x2A => ("ROL", Accumulator),
+        0x2B => ("ANC", Immediate),
+        0x2C => ("BIT", Absolute),
+        0x2D => ("AND", Absolute),
+        0x2E => ("ROL", Absolute),
+        0x2F => ("RLA", Absolute),
+        0x30 => ("BMI", Relative),
+        0x31 => ("AND", IndirectY),
+        0x32 => ("JAM", Implied),
+        0x33 => ("RLA", IndirectY),
+        0x34 => ("NOP", ZeroX),
+        0x35 => ("AND", ZeroX),
+        0x36 => ("ROL", ZeroX),
+        0x37 => ("RLA", ZeroX),
+        0x38 => ("SEC", Implied),
+        0x39 => ("AND", AbsoluteY),
+        0x3A => ("NOP", Implied),
+        0x3B => ("RLA", AbsoluteY),
+        0x3C => ("NOP", AbsoluteX),
+        0x3D => ("AND", AbsoluteX),
+        0x3E => ("ROL", AbsoluteX),
+        0x3F => ("RLA", AbsoluteX),
+        0x40 => ("RTI", Implied),
+        0x41 => ("EOR", IndirectX),
+        0x42 => ("JAM", Implied),
+        0x43 => ("SRE", IndirectX),
+        0x44 => ("NOP", Zero),
+        0x45 => ("EOR", Zero),
+        0x46 => ("LSR", Zero),
+        0x47 => ("SRE", Zero),
+        0x48 => ("PHA", Implied),
+        0x49 => ("EOR", Immediate),
+        0x4A => ("LSR", Accumulator),
+        0x4B => ("ASR", Immediate),
+        0x4C => ("JMP", Absolute),
+        0x4D => ("EOR", Absolute),
+        0x4E => ("LSR", Absolute),
+        0x4F => ("SRE", Absolute),
+        0x50 => ("BVC", Relative),
+        0x51 => ("EOR", IndirectY),
+        0x52 => ("JAM", Implied),
+        0x53 => ("SRE", IndirectY),
+        0x54 => ("NOP", ZeroX),
+        0x55 => ("EOR", ZeroX),
+        0x56 => ("LSR", ZeroX),
+        0x57 => ("SRE", ZeroX),
+        0x58 => ("CLI", Implied),
+        0x59 => ("EOR", AbsoluteY),
+        0x5A => ("NOP", Implied),
+        0x5B => ("SRE", AbsoluteY),
+        0x5C => ("NOP", AbsoluteX),
+        0x5D => ("EOR", AbsoluteX),
+        0x5E => ("LSR", AbsoluteX),
+        0x5F => ("SRE", AbsoluteX),
+        0x60 => ("RTS", Implied),
+        0x61 => ("ADC", IndirectX),
+        0x62 => ("JAM", Implied),
+        0x63 => ("RRA", IndirectX),
+        0x64 => ("NOP", Zero),
+        0x65 => ("ADC", Zero),
+        0x66 => ("ROR", Zero),
+        0x67 => ("RRA", Zero),
+        0x68 => ("PLA", Implied),
+        0x69 => ("ADC", Immediate),
+        0x6A => ("ROR", Accumulator),
+        0x6B => ("ARR", Immediate),
+        0x6C => ("JMP", Indirect),
+        0x6D => ("ADC", Absolute),
+        0x6E => ("ROR", Absolute),
+        0x6F => ("RRA", Absolute),
+        0x70 => ("BVS", Relative),
+        0x71 => ("ADC", IndirectY),
+        0x72 => ("JAM", Implied),
+        0x73 => ("RRA", IndirectY),
+        0x74 => ("NOP", ZeroX),
+        0x75 => ("ADC", ZeroX),
+        0x76 => ("ROR", ZeroX),
+        0x77 => ("RRA", ZeroX),
+        0x78 => ("SEI", Implied),
+        0x79 => ("ADC", AbsoluteY),
+        0x7A => ("NOP", Implied),
+        0x7B => ("RRA", AbsoluteY),
+        0x7C => ("NOP", AbsoluteX),
+        0x7D => ("ADC", AbsoluteX),
+        0x7E => ("ROR", AbsoluteX),
+        0x7F => ("RRA", AbsoluteX),
+        0x80 => ("NOP", Immediate),
+        0x81 => ("STA", IndirectX),
+        0x82 => ("NOP", Immediate),
+        0x83 => ("SAX", IndirectX),
+        0x84 => ("STY", Zero),
+        0x85 => ("STA", Zero),
+        0x86 => ("STX", Zero),
+        0x87 => ("SAX", Zero),
+        0x88 => ("DEY", Implied),
+        0x89 => ("NOP", Immediate),
+        0x8A => ("TXA", Implied),
+        0x8B => ("ANE", Immediate),
+        0x8C => ("STY", Absolute),
+        0x8D => ("STA", Absolute),
+        0x8E => ("STX", Absolute),
+        0x8F => ("SAX", Absolute),
+        0x90 => ("BCC", Relative),
+        0x91 => ("STA", IndirectY),
+        0x92 => ("JAM", Implied),
+        0x93 => ("SHA", IndirectY),
+        0x94 => ("STY", ZeroX),
+        0x95 => ("STA", ZeroX),
+        0x96 => ("STX", ZeroY),
+        0x97 => ("SAX", ZeroY),
+        0x98 => ("TYA", Implied),
+        0x99 => ("STA", AbsoluteY),
+        0x9A => ("TXS", Implied),
+        0x9B => ("SHS", AbsoluteY),
+        0x9C => ("SHY", AbsoluteX),
+        0x9D => ("STA", AbsoluteX),
+        0x9E => ("SHX", AbsoluteY),
+        0x9F => ("SHA", AbsoluteY),
+        0xA0 => ("LDY", Immediate),
+        0xA1 => ("LDA", IndirectX),
+        0xA2 => ("LDX", Immediate),
+        0xA3 => ("LAX", IndirectX),
+        0xA4 => ("LDY", Zero),
+        0xA5 => ("LDA", Zero),
+        0xA6 => ("LDX", Zero),
+        0xA7 => ("LAX", Zero),
+        0xA8 => ("TAY", Implied),
+        0xA9 => ("LDA", Immediate),
+        0xAA => ("TAX", Implied),
+        0xAB => ("LXA", Immediate),
+        0xAC => ("LDY", Absolute),
+        0xAD => ("LDA", Absolute),
+        0xAE => ("LDX", Absolute),
+        0xAF => ("LAX", Absolute),
+        0xB0 => ("BCS", Relative),
+        0xB1 => ("LDA", IndirectY),
+        0xB2 => ("JAM", Implied),
+        0xB3 => ("LAX", IndirectY),
+        0xB4 => ("LDY", ZeroX),
+        0xB5 => ("LDA", ZeroX),
+        0xB6 => ("LDX", ZeroY),
+        0xB7 => ("LAX", ZeroY),
+        0xB8 => ("CLV", Implied),
+        0xB9 => ("LDA", AbsoluteY),
+        0xBA => ("TSX", Implied),
+        0xBB => ("LAS", AbsoluteY),
+        0xBC => ("LDY", AbsoluteX),
+        0xBD => ("LDA", AbsoluteX),
+        0xBE => ("LDX", AbsoluteY),
+        0xBF => ("LAX", AbsoluteY),
+        0xC0 => ("CPY", Immediate),
+        0xC1 => ("CMP", IndirectX),
+        0xC2 => ("NOP", Immediate),
+        0xC3 => ("DCP", IndirectX),
+        0xC4 => ("CPY", Zero),
+        0xC5 => ("CMP", Zero),
+        0xC6 => ("DEC", Zero),
+        0xC7 => ("DCP", Zero),
+        0xC8 => ("INY", Implied),
+        0xC9 => ("CMP", Immediate),
+        0xCA => ("DEX", Implied),
+        0xCB => ("SBX", Immediate),
+        0xCC => ("CPY", Absolute),
+        0xCD => ("CMP", Absolute),
+        0xCE => ("DEC", Absolute),
+        0xCF => ("DCP", Absolute),
+        0xD0 => ("BNE", Relative),
+        0xD1 => ("CMP", IndirectY),
+        0xD2 => ("JAM", Implied),
+        0xD3 => ("DCP", IndirectY),
+        0xD4 => ("NOP", ZeroX),
+        0xD5 => ("CMP", ZeroX),
+        0xD6 => ("DEC", ZeroX),
+        0xD7 => ("DCP", ZeroX),
+        0xD8 => ("CLD", Implied),
+        0xD9 => ("CMP", AbsoluteY),
+        0xDA => ("NOP", Implied),
+        0xDB => ("DCP", AbsoluteY),
+        0xDC => ("NOP", AbsoluteX),
+        0xDD => ("CMP", AbsoluteX),
+        0xDE => ("DEC", AbsoluteX),
+        0xDF => ("DCP", AbsoluteX),
+        0xE0 => ("CPX", Immediate),
+        0xE1 => ("SBC", IndirectX),
+        0xE2 => ("NOP", Immediate),
+        0xE3 => ("ISB", IndirectX),
+        0xE4 => ("CPX", Zero),
+        0xE5 => ("SBC", Zero),
+        0xE6 => ("INC", Zero),
+        0xE7 => ("ISB", Zero),
+        0xE8 => ("INX", Implied),
+        0xE9 => ("SBC", Immediate),
+        0xEA => ("NOP", Implied),
+        0xEB => ("SBC", Immediate),
+        0xEC => ("CPX", Absolute),
+        0xED => ("SBC", Absolute),
+        0xEE => ("INC", Absolute),
+        0xEF => ("ISB", Absolute),
+        0xF0 => ("BEQ", Relative),
+        0xF1 => ("SBC", IndirectY),
+        0xF2 => ("JAM", Implied),
+        0xF3 => ("ISB", IndirectY),
+        0xF4 => ("NOP", ZeroX),
+        0xF5 => ("SBC", ZeroX),
+        0xF6 => ("INC", ZeroX),
+        0xF7 => ("ISB", ZeroX),
+        0xF8 => ("SED", Implied),
+        0xF9 => ("SBC", AbsoluteY),
+        0xFA => ("NOP", Implied),
+        0xFB => ("ISB", AbsoluteY),
+        0xFC => ("NOP", AbsoluteX),
+        0xFD => ("SBC", AbsoluteX),
+        0xFE => ("INC", AbsoluteX),
+        0xFF => ("ISB", AbsoluteX),
+    }
+}
+
+/// Number of bytes (opcode + operand) an addressing mode occupies in the instruction stream.
+fn mode_len(mode: AddrMode) -> u8 {
+    match mode {
+        Implied | Accumulator => 1,
+        Immediate | Zero | ZeroX | ZeroY | IndirectX | IndirectY | Relative => 2,
+        Absolute | AbsoluteX | AbsoluteY | Indirect => 3,
+        Auto => 1,
+    }
+}
+
+/// Disassemble the instruction at `pc`, returning its length in bytes and a nestest-style
+/// `MNEMONIC OPERAND` string (e.g. `LDA $0200,X`, `BNE $C5F5`).
+///
+/// Operand bytes are read directly off the CPU bus via [`Nes::read`], the same as a real
+/// instruction fetch would; since they always fall within already-mapped program code, this is
+/// side-effect-free in practice (the interactive debugger's `read` command relies on the same
+/// assumption). Branch targets are resolved relative to the end of the instruction, matching how
+/// [`branch`] computes them at execution time.
+pub fn disassemble(nes: &mut Nes, pc: u16) -> (u8, String) {
+    let opcode = nes.read(pc);
+    let (mnemonic, mode) = decode_info(opcode);
+    let len = mode_len(mode);
+
+    let operand = match mode {
+        Implied | Accumulator | Auto => String::new(),
+        Immediate => format!("#${:02X}", nes.read(pc.wrapping_add(1))),
+        Zero => format!("${:02X}", nes.read(pc.wrapping_add(1))),
+        ZeroX => format!("${:02X},X", nes.read(pc.wrapping_add(1))),
+        ZeroY => format!("${:02X},Y", nes.read(pc.wrapping_add(1))),
+        IndirectX => format!("(${:02X},X)", nes.read(pc.wrapping_add(1))),
+        IndirectY => format!("(${:02X}),Y", nes.read(pc.wrapping_add(1))),
+        Relative => {
+            let offset = nes.read(pc.wrapping_add(1)) as i8;
+            let target = (pc.wrapping_add(2) as i16).wrapping_add(offset as i16) as u16;
+            format!("${:04X}", target)
+        },
+        Absolute => format!("${:04X}", addr16(nes, pc)),
+        AbsoluteX => format!("${:04X},X", addr16(nes, pc)),
+        AbsoluteY => format!("${:04X},Y", addr16(nes, pc)),
+        Indirect => format!("(${:04X})", addr16(nes, pc)),
+    };
+
+    (len, if operand.is_empty() { mnemonic.to_string() } else { format!("{mnemonic} {operand}") })
+}
+
+fn addr16(nes: &mut Nes, pc: u16) -> u16 {
+    let lo = nes.read(pc.wrapping_add(1));
+    let hi = nes.read(pc.wrapping_add(2));
+    ((hi as u16) << 8) | (lo as u16)
+}
+
+/// Render a [`TestState`] snapshot as one Nintendulator-format trace line (the format `nestest.log`
+/// and most other reference 6502 traces use): PC, raw instruction bytes, disassembly, registers,
+/// PPU position (scanline, then dot), and CPU cycle count. The PPU position is `nes`'s current
+/// one, since [`TestState`] doesn't record it.
+///
+/// ```text
+/// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+/// ```
+pub fn format_trace_line(nes: &mut Nes, state: &TestState) -> String {
+    let (len, text) = disassemble(nes, state.pc);
+
+    let mut bytes = String::new();
+    for offset in 0..len {
+        if offset > 0 {
+            bytes.push(' ');
+        }
+        bytes.push_str(&format!("{:02X}", nes.read(state.pc.wrapping_add(offset as u16))));
+    }
+
+    format!(
+        "{:04X}  {:<8}  {:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3} CYC:{}",
+        state.pc, bytes, text, state.acc, state.x, state.y, state.status, state.sp,
+        nes.ppu.pos.scanline, nes.ppu.pos.cycle, state.cyc,
+    )
+}
@@ -0,0 +1,322 @@
+//! GDB Remote Serial Protocol server, letting a real debugger (GDB, LLDB) attach to a running
+//! [`Nes`] over TCP via the `gdbstub` crate.
+//!
+//! [`NesTarget`] is the adapter `gdbstub` drives: it borrows a [`Nes`] and translates the 6502's
+//! register layout and 16-bit address space to the protocol. Stepping is driven through
+//! [`Cpu::step_instruction`]/[`Nes::tick`], and memory accesses are serviced through the existing
+//! [`Nes::read`]/[`Nes::write`]. Execution breakpoints reuse [`Cpu::breakpoints`]; read/write
+//! watchpoints are tracked here and checked against [`Nes::last_bus`] after every tick, matching
+//! how the rest of the core observes bus activity rather than intercepting it.
+//!
+//! Entirely opt-in behind the `gdbstub` cargo feature, so the core stays free of the dependency
+//! (and the TCP listener) unless a caller asks for it.
+
+use std::net::{TcpListener, TcpStream};
+use gdbstub::arch::{Arch, Registers};
+use gdbstub::common::Signal;
+use gdbstub::conn::ConnectionExt;
+use gdbstub::stub::{DisconnectReason, GdbStub, GdbStubStateMachine, SingleThreadStopReason};
+use gdbstub::target::ext::base::singlethread::{SingleThreadBase, SingleThreadResume, SingleThreadSingleStep};
+use gdbstub::target::ext::base::BaseOps;
+use gdbstub::target::ext::breakpoints::{Breakpoints, SwBreakpoint, WatchKind, HwWatchpoint};
+use gdbstub::target::{Target, TargetError, TargetResult};
+use crate::arch::cpu::{Cpu, StatusReg};
+use crate::arch::Nes;
+
+/// Register file for the 6502, in the order `gdbstub` expects a target description to list them:
+/// `pc`, `a`, `x`, `y`, `sp`, `p` (the status register).
+///
+/// There's no built-in 6502 arch in `gdbstub_arch`, so this and [`Mos6502`] define just enough of
+/// one by hand to serve `info registers`/`p $reg` over the wire.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Mos6502Registers {
+    pub pc: u16,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub p: u8,
+}
+impl Registers for Mos6502Registers {
+    type ProgramCounter = u16;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for byte in self.pc.to_le_bytes() {
+            write_byte(Some(byte));
+        }
+        for byte in [self.a, self.x, self.y, self.sp, self.p] {
+            write_byte(Some(byte));
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() < 7 {
+            return Err(());
+        }
+
+        self.pc = u16::from_le_bytes([bytes[0], bytes[1]]);
+        self.a = bytes[2];
+        self.x = bytes[3];
+        self.y = bytes[4];
+        self.sp = bytes[5];
+        self.p = bytes[6];
+
+        Ok(())
+    }
+}
+
+/// Minimal hand-rolled `gdbstub` [`Arch`] for the 6502: a 16-bit address space and the register
+/// file above, with no extra target-description XML.
+#[derive(Debug)]
+pub struct Mos6502;
+impl Arch for Mos6502 {
+    type Usize = u16;
+    type Registers = Mos6502Registers;
+    type RegId = ();
+    type BreakpointKind = usize;
+}
+
+/// What stopped [`NesTarget::run_until_stop`]: a breakpoint, an armed watchpoint's address firing,
+/// or the caller-supplied cycle budget running out without either.
+enum StopCause {
+    Breakpoint,
+    Watchpoint { addr: u16, kind: WatchKind },
+    Budget,
+}
+
+/// `gdbstub`'s `WatchKind` doesn't derive `PartialEq`/`Hash`, so watchpoint (de)registration
+/// compares kinds by hand instead of via `==`.
+fn watch_kind_eq(a: WatchKind, b: WatchKind) -> bool {
+    matches!(
+        (a, b),
+        (WatchKind::Write, WatchKind::Write)
+            | (WatchKind::Read, WatchKind::Read)
+            | (WatchKind::ReadWrite, WatchKind::ReadWrite)
+    )
+}
+
+/// `gdbstub` adapter borrowing a running [`Nes`]. Construct one per debug session and drive it
+/// through [`GdbStub::run_state_machine`] (see [`serve`]).
+pub struct NesTarget<'a> {
+    nes: &'a mut Nes,
+    /// `gdbstub`'s `WatchKind` doesn't implement `Hash`, so this is a linear-scanned `Vec` rather
+    /// than a `HashSet`; the handful of watchpoints a debugger session sets makes that fine.
+    watchpoints: Vec<(u16, WatchKind)>,
+}
+impl<'a> NesTarget<'a> {
+    pub fn new(nes: &'a mut Nes) -> Self {
+        Self { nes, watchpoints: Vec::new() }
+    }
+
+    /// Run until a breakpoint/watchpoint fires or `max_cycles` master cycles elapse, whichever
+    /// comes first -- the latter just bounds how long a single `resume` call blocks before
+    /// `gdbstub` gets to poll for an incoming Ctrl-C.
+    fn run_until_stop(&mut self, max_cycles: usize) -> StopCause {
+        for _ in 0..max_cycles {
+            if self.nes.tick() {
+                return StopCause::Breakpoint;
+            }
+
+            let bus = self.nes.last_bus;
+            for &(addr, kind) in &self.watchpoints {
+                let matches = addr == bus.addr && match kind {
+                    WatchKind::Write => !bus.is_read,
+                    WatchKind::Read => bus.is_read,
+                    WatchKind::ReadWrite => true,
+                };
+                if matches {
+                    return StopCause::Watchpoint { addr, kind };
+                }
+            }
+        }
+
+        StopCause::Budget
+    }
+}
+impl<'a> Target for NesTarget<'a> {
+    type Arch = Mos6502;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> BaseOps<'_, Self::Arch, Self::Error> {
+        BaseOps::SingleThread(self)
+    }
+
+    #[inline(always)]
+    fn support_breakpoints(&mut self) -> Option<gdbstub::target::ext::breakpoints::BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+}
+impl<'a> SingleThreadBase for NesTarget<'a> {
+    fn read_registers(&mut self, regs: &mut Mos6502Registers) -> TargetResult<(), Self> {
+        *regs = Mos6502Registers {
+            pc: self.nes.cpu.pc,
+            a: self.nes.cpu.acc,
+            x: self.nes.cpu.x,
+            y: self.nes.cpu.y,
+            sp: self.nes.cpu.sp.0,
+            p: self.nes.cpu.status.bits(),
+        };
+
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &Mos6502Registers) -> TargetResult<(), Self> {
+        self.nes.cpu.pc = regs.pc;
+        self.nes.cpu.acc = regs.a;
+        self.nes.cpu.x = regs.x;
+        self.nes.cpu.y = regs.y;
+        self.nes.cpu.sp.0 = regs.sp;
+        self.nes.cpu.status = StatusReg::from_bits_truncate(regs.p);
+
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u16, data: &mut [u8]) -> TargetResult<usize, Self> {
+        for (offset, byte) in data.iter_mut().enumerate() {
+            *byte = self.nes.read(start_addr.wrapping_add(offset as u16));
+        }
+
+        Ok(data.len())
+    }
+
+    fn write_addrs(&mut self, start_addr: u16, data: &[u8]) -> TargetResult<(), Self> {
+        for (offset, &byte) in data.iter().enumerate() {
+            self.nes.write(start_addr.wrapping_add(offset as u16), byte);
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn support_resume(&mut self) -> Option<gdbstub::target::ext::base::singlethread::SingleThreadResumeOps<'_, Self>> {
+        Some(self)
+    }
+}
+impl<'a> SingleThreadResume for NesTarget<'a> {
+    fn resume(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn support_single_step(&mut self) -> Option<gdbstub::target::ext::base::singlethread::SingleThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+}
+impl<'a> SingleThreadSingleStep for NesTarget<'a> {
+    fn step(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        Cpu::step_instruction(self.nes);
+        Ok(())
+    }
+}
+impl<'a> Breakpoints for NesTarget<'a> {
+    #[inline(always)]
+    fn support_sw_breakpoint(&mut self) -> Option<gdbstub::target::ext::breakpoints::SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    #[inline(always)]
+    fn support_hw_watchpoint(&mut self) -> Option<gdbstub::target::ext::breakpoints::HwWatchpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+impl<'a> SwBreakpoint for NesTarget<'a> {
+    fn add_sw_breakpoint(&mut self, addr: u16, _kind: usize) -> TargetResult<bool, Self> {
+        Ok(self.nes.cpu.breakpoints.insert(addr))
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u16, _kind: usize) -> TargetResult<bool, Self> {
+        Ok(self.nes.cpu.breakpoints.remove(&addr))
+    }
+}
+impl<'a> HwWatchpoint for NesTarget<'a> {
+    fn add_hw_watchpoint(&mut self, addr: u16, _len: u16, kind: WatchKind) -> TargetResult<bool, Self> {
+        if self.watchpoints.iter().any(|&(a, k)| a == addr && watch_kind_eq(k, kind)) {
+            return Ok(false);
+        }
+
+        self.watchpoints.push((addr, kind));
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(&mut self, addr: u16, _len: u16, kind: WatchKind) -> TargetResult<bool, Self> {
+        match self.watchpoints.iter().position(|&(a, k)| a == addr && watch_kind_eq(k, kind)) {
+            Some(idx) => {
+                self.watchpoints.remove(idx);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Number of master cycles [`NesTarget::run_until_stop`] runs per `resume`/`continue` poll before
+/// checking back in with `gdbstub` for an incoming interrupt; arbitrary, just small enough to
+/// keep Ctrl-C responsive.
+const CYCLES_PER_POLL: usize = 1 << 16;
+
+/// Wrap any connection/protocol error [`serve`]'s state-machine loop hits into a plain
+/// [`std::io::Error`], so the whole loop can bail out with `?`.
+fn to_io_err<E: std::fmt::Display>(err: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, err.to_string())
+}
+
+/// Block on one TCP connection at `addr` (e.g. `"127.0.0.1:9001"`) and serve the GDB Remote
+/// Serial Protocol against `nes` until the debugger detaches.
+///
+/// Driven by hand through `gdbstub`'s state machine rather than `GdbStub::run_blocking`:
+/// `run_blocking` requires a `BlockingEventLoop::Target` with no borrowed lifetime, but
+/// `NesTarget<'a>` only ever borrows `nes` for this one call, so there's no owned/`'static`
+/// target to hand it. Pumping the state machine ourselves lets `target` stay borrowed locally
+/// instead.
+pub fn serve(nes: &mut Nes, addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let (stream, _) = listener.accept()?;
+    stream.set_nodelay(true)?;
+
+    let connection: Box<dyn ConnectionExt<Error = std::io::Error>> = Box::new(stream);
+    let mut target = NesTarget::new(nes);
+
+    let mut gdb = GdbStub::new(connection)
+        .run_state_machine(&mut target)
+        .map_err(to_io_err)?;
+
+    loop {
+        gdb = match gdb {
+            GdbStubStateMachine::Idle(mut state) => {
+                let byte = state.borrow_conn().read().map_err(to_io_err)?;
+                state.incoming_data(&mut target, byte).map_err(to_io_err)?
+            }
+            GdbStubStateMachine::Running(mut state) => {
+                if state.borrow_conn().peek().map_err(to_io_err)?.is_some() {
+                    let byte = state.borrow_conn().read().map_err(to_io_err)?;
+                    state.incoming_data(&mut target, byte).map_err(to_io_err)?
+                } else {
+                    match target.run_until_stop(CYCLES_PER_POLL) {
+                        StopCause::Breakpoint => state
+                            .report_stop(&mut target, SingleThreadStopReason::SwBreak(()))
+                            .map_err(to_io_err)?,
+                        StopCause::Watchpoint { addr, kind } => state
+                            .report_stop(&mut target, SingleThreadStopReason::Watch { tid: (), kind, addr })
+                            .map_err(to_io_err)?,
+                        StopCause::Budget => GdbStubStateMachine::Running(state),
+                    }
+                }
+            }
+            GdbStubStateMachine::CtrlCInterrupt(state) => state
+                .interrupt_handled(&mut target, Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+                .map_err(to_io_err)?,
+            GdbStubStateMachine::Disconnected(state) => {
+                return match state.get_reason() {
+                    DisconnectReason::Disconnect
+                    | DisconnectReason::TargetExited(_)
+                    | DisconnectReason::TargetTerminated(_)
+                    | DisconnectReason::Kill => Ok(()),
+                };
+            }
+        };
+    }
+}
@@ -1,8 +1,17 @@
 use std::fmt::Debug;
 use dyn_clone::DynClone;
+use serde::{Deserialize, Serialize};
 use crate::arch::mappers::mapper000::Mapper000;
+use crate::arch::mappers::mapper001::Mapper001;
+use crate::arch::mappers::mapper002::Mapper002;
+use crate::arch::mappers::mapper003::Mapper003;
+use crate::arch::mappers::mapper007::Mapper007;
 
 pub mod mapper000;
+pub mod mapper001;
+pub mod mapper002;
+pub mod mapper003;
+pub mod mapper007;
 
 #[allow(unused_variables)]
 pub trait Mapper: DynClone + Debug {
@@ -14,8 +23,11 @@ pub trait Mapper: DynClone + Debug {
         0 //todo: open bus behavior
     }
     
-    /// Write access on PRG bus.
-    fn write_cpu(&mut self, addr: u16, data: u8) {}
+    /// Write access on PRG bus. `cyc` is the CPU's current CPU-cycle count
+    /// ([`Cpu::cyc`](crate::arch::cpu::Cpu::cyc)), passed through so mappers with write-timing
+    /// quirks (e.g. MMC1's one-write-per-two-cycles serial port) can detect back-to-back writes
+    /// without tracking their own clock.
+    fn write_cpu(&mut self, addr: u16, data: u8, cyc: usize) {}
     
     /// Read access on CHR bus.
     fn read_ppu(&mut self, addr: u16) -> u8 {
@@ -24,12 +36,95 @@ pub trait Mapper: DynClone + Debug {
     
     /// Write access on CHR bus.
     fn write_ppu(&mut self, addr: u16, data: u8) {}
-    
-    //fn ciram_a10(&self) -> bool {}
-    //fn ciram__ce(&self) -> bool {}
+
+    /// Current nametable mirroring arrangement, as driven by the cartridge's CIRAM A10 line.
+    ///
+    /// Mappers that hardcode their wiring (or never change it) can rely on the default; mappers
+    /// that can switch mirroring at runtime (e.g. MMC1, AxROM) should override this.
+    fn mirroring(&self) -> Mirroring {
+        Mirroring::Horizontal
+    }
+
+    /// Read from the cartridge's own nametable VRAM, present only on four-screen carts.
+    fn read_nametable(&mut self, addr: u16) -> u8 {
+        panic!("cartridge does not provide four-screen nametable VRAM (addr {:#06X})", addr)
+    }
+
+    /// Write to the cartridge's own nametable VRAM, present only on four-screen carts.
+    fn write_nametable(&mut self, addr: u16, data: u8) {
+        panic!("cartridge does not provide four-screen nametable VRAM (addr {:#06X}, data {:#04X})", addr, data)
+    }
+
+    /// Snapshot this mapper's state for save-state serialization.
+    fn save_state(&self) -> MapperState {
+        panic!("this mapper does not yet support save states")
+    }
 }
 dyn_clone::clone_trait_object!(Mapper);
 
+/// Serializable snapshot of a cartridge's mapper state, closed over the set of mappers this
+/// emulator implements (mirroring the match in [`RomFile::into_mapper`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MapperState {
+    Mapper000(Mapper000),
+    Mapper001(Mapper001),
+    Mapper002(Mapper002),
+    Mapper003(Mapper003),
+    Mapper007(Mapper007),
+}
+impl MapperState {
+    pub fn into_mapper(self) -> Box<dyn Mapper> {
+        match self {
+            MapperState::Mapper000(m) => Box::new(m),
+            MapperState::Mapper001(m) => Box::new(m),
+            MapperState::Mapper002(m) => Box::new(m),
+            MapperState::Mapper003(m) => Box::new(m),
+            MapperState::Mapper007(m) => Box::new(m),
+        }
+    }
+}
+
+/// Arrangement of the PPU's two physical nametables across its 4-screen logical address space,
+/// as selected by the cartridge's CIRAM A10/A11 wiring (or lack thereof).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    SingleScreenLo,
+    SingleScreenHi,
+    FourScreen,
+}
+impl Mirroring {
+    /// Resolve one of the four logical 1 KiB nametables (`table` in `0..=3`) to a physical
+    /// storage slot: one of the PPU's two internal CIRAM banks, or (for four-screen carts only)
+    /// one of the cartridge's own extra VRAM banks.
+    pub fn resolve(&self, table: u16) -> NametableSlot {
+        use NametableSlot::*;
+        match self {
+            Mirroring::Horizontal => Ciram((table >> 1) as u8 & 0x01),
+            Mirroring::Vertical => Ciram(table as u8 & 0x01),
+            Mirroring::SingleScreenLo => Ciram(0),
+            Mirroring::SingleScreenHi => Ciram(1),
+            Mirroring::FourScreen => if table < 2 { Ciram(table as u8) } else { CartVram(table as u8 - 2) },
+        }
+    }
+}
+
+/// CPU/PPU timing region, as declared by an NES 2.0 header's byte 12 (see [`RomFile::timing`]).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RomTiming {
+    Ntsc,
+    Pal,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NametableSlot {
+    Ciram(u8),
+    CartVram(u8),
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct DummyMapper {}
 impl Mapper for DummyMapper {
@@ -58,13 +153,11 @@ impl RomFile {
             rom.trainer = Some(data[ptr..(ptr + 512)].try_into().unwrap());
             ptr += 512;
         }
-        
-        let units = data[4] as usize;
-        rom.prg = data[ptr..(ptr + (16384 * units))].to_vec();
+
+        rom.prg = data[ptr..(ptr + rom.prg_rom_size())].to_vec();
         ptr += rom.prg.len();
-        
-        let units = data[5] as usize;
-        rom.chr = data[ptr..(ptr + (8192 * units))].to_vec();
+
+        rom.chr = data[ptr..(ptr + rom.chr_rom_size())].to_vec();
         ptr += rom.chr.len();
         
         if data[7] & 0x02 != 0 {
@@ -92,11 +185,129 @@ impl RomFile {
             ((self.header[7] & 0xF0) | (self.header[6] >> 4)) as u16
         }
     }
-    
+
+    /// Default nametable mirroring wired by the cartridge, per header byte 6. Mappers that can
+    /// switch mirroring at runtime ignore this and report their own current state instead.
+    #[inline(always)]
+    pub fn mirroring(&self) -> Mirroring {
+        if self.header[6] & 0x08 != 0 {
+            Mirroring::FourScreen
+        } else if self.header[6] & 0x01 != 0 {
+            Mirroring::Vertical
+        } else {
+            Mirroring::Horizontal
+        }
+    }
+
+    /// Size, in bytes, of the PRG-ROM area, decoded from header bytes 4/9.
+    ///
+    /// Under NES 2.0, a PRG-size nibble of `0xF` switches byte 4 from a unit count to an
+    /// exponent-multiplier: `2^exponent * (multiplier*2 + 1)`, with `exponent` in the top six
+    /// bits and `multiplier` in the bottom two.
+    #[inline(always)]
+    pub fn prg_rom_size(&self) -> usize {
+        if self.is_ines2() {
+            let msb = self.header[9] & 0x0F;
+            if msb == 0x0F {
+                let byte = self.header[4];
+                let multiplier = (byte & 0x03) as usize;
+                let exponent = (byte >> 2) as u32;
+                (1usize << exponent) * (multiplier * 2 + 1)
+            } else {
+                (((msb as usize) << 8) | self.header[4] as usize) * 16384
+            }
+        } else {
+            self.header[4] as usize * 16384
+        }
+    }
+
+    /// Size, in bytes, of the CHR-ROM area, decoded from header bytes 5/9.
+    ///
+    /// Same exponent-multiplier notation as [`RomFile::prg_rom_size`], gated by the CHR-size
+    /// nibble in byte 9's upper half instead.
+    #[inline(always)]
+    pub fn chr_rom_size(&self) -> usize {
+        if self.is_ines2() {
+            let msb = self.header[9] >> 4;
+            if msb == 0x0F {
+                let byte = self.header[5];
+                let multiplier = (byte & 0x03) as usize;
+                let exponent = (byte >> 2) as u32;
+                (1usize << exponent) * (multiplier * 2 + 1)
+            } else {
+                (((msb as usize) << 8) | self.header[5] as usize) * 8192
+            }
+        } else {
+            self.header[5] as usize * 8192
+        }
+    }
+
+    /// Size, in bytes, of battery-backed PRG-NVRAM, decoded from the NES 2.0 shift count in
+    /// byte 10's upper nibble. Always `0` for iNES 1.0 headers, which can't express NVRAM size.
+    #[inline(always)]
+    pub fn prg_nvram_size(&self) -> usize {
+        Self::shift_count_size(if self.is_ines2() { self.header[10] >> 4 } else { 0 })
+    }
+
+    /// Size, in bytes, of volatile PRG-RAM, decoded from the NES 2.0 shift count in byte 10's
+    /// lower nibble. Always `0` for iNES 1.0 headers.
+    #[inline(always)]
+    pub fn prg_ram_size(&self) -> usize {
+        Self::shift_count_size(if self.is_ines2() { self.header[10] & 0x0F } else { 0 })
+    }
+
+    /// Size, in bytes, of battery-backed CHR-NVRAM, decoded from the NES 2.0 shift count in
+    /// byte 11's upper nibble. Always `0` for iNES 1.0 headers.
+    #[inline(always)]
+    pub fn chr_nvram_size(&self) -> usize {
+        Self::shift_count_size(if self.is_ines2() { self.header[11] >> 4 } else { 0 })
+    }
+
+    /// Size, in bytes, of volatile CHR-RAM, decoded from the NES 2.0 shift count in byte 11's
+    /// lower nibble. Always `0` for iNES 1.0 headers.
+    #[inline(always)]
+    pub fn chr_ram_size(&self) -> usize {
+        Self::shift_count_size(if self.is_ines2() { self.header[11] & 0x0F } else { 0 })
+    }
+
+    /// Decode an NES 2.0 RAM/NVRAM shift count (`0` meaning "not present", otherwise `64 << shift`).
+    #[inline(always)]
+    fn shift_count_size(shift: u8) -> usize {
+        if shift == 0 { 0 } else { 64usize << shift }
+    }
+
+    /// Submapper number, decoded from the NES 2.0 nibble in byte 8's upper half. Always `0` for
+    /// iNES 1.0 headers, which have no submapper field.
+    #[inline(always)]
+    pub fn submapper(&self) -> u8 {
+        if self.is_ines2() { self.header[8] >> 4 } else { 0 }
+    }
+
+    /// CPU/PPU timing region, decoded from the NES 2.0 field in byte 12's low two bits. Always
+    /// [`RomTiming::Ntsc`] for iNES 1.0 headers, which have no timing field.
+    #[inline(always)]
+    pub fn timing(&self) -> RomTiming {
+        if !self.is_ines2() {
+            return RomTiming::Ntsc;
+        }
+
+        match self.header[12] & 0x03 {
+            0 => RomTiming::Ntsc,
+            1 => RomTiming::Pal,
+            2 => RomTiming::MultiRegion,
+            3 => RomTiming::Dendy,
+            _ => unreachable!(),
+        }
+    }
+
     pub fn into_mapper(self) -> Box<dyn Mapper> {
         match self.mapper_number() {
             000 => Mapper000::new(self),
-            
+            001 => Mapper001::new(self),
+            002 => Mapper002::new(self),
+            003 => Mapper003::new(self),
+            007 => Mapper007::new(self),
+
             _ => panic!("Failed to detect ROM mapper type! Possibly unsupported.")
         }
     }
@@ -1,41 +1,56 @@
 
 //! iNES 000
 
-use crate::arch::mappers::{Mapper, RomFile};
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
+use crate::arch::mappers::{Mapper, MapperState, Mirroring, RomFile};
 
 /// Alias for mapper number 000
 pub type NRom = Mapper000;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mapper000 {
     /// Family Basic only, but seems most emus provide 8 KiB?
-    pub prg_ram: [u8; 0x2000],
+    pub prg_ram: Vec<u8>,
+    #[serde(with = "BigArray")]
     pub prg_rom: [u8; 0x8000],
-    pub chr_rom: [u8; 0x2000],
+    pub chr: Vec<u8>,
+    chr_is_ram: bool,
+    pub mirroring: Mirroring,
     //pub ciram_a10: bool,
     //pub ciram__ce: bool,
 }
 impl Mapper for Mapper000 {
     fn new(rom: RomFile) -> Box<dyn Mapper> {
+        let mirroring = rom.mirroring();
+        let chr_is_ram = rom.chr.is_empty();
+        let prg_ram = vec![0u8; rom.prg_ram_size().max(0x2000)];
+        let chr = if chr_is_ram {
+            vec![0u8; rom.chr_ram_size().max(0x2000)]
+        } else {
+            let mut data = rom.chr.clone();
+            data.resize(0x2000, 0);
+
+            data
+        };
+        let prg_rom = if rom.prg.len() == 0x4000 {
+            let mut data = rom.prg.to_vec();
+            data.extend_from_slice(&rom.prg);
+
+            data.try_into().unwrap()
+        } else {
+            rom.prg.try_into().unwrap()
+        };
+
         Box::new(Mapper000 {
-            prg_ram: [0u8; 0x2000],
-            prg_rom: if rom.prg.len() == 0x4000 {
-                let mut data = rom.prg.to_vec();
-                data.extend_from_slice(&rom.prg);
-                
-                data.try_into().unwrap()
-            } else {
-                rom.prg.try_into().unwrap()
-            },
-            chr_rom: {
-                let mut data = rom.chr.clone();
-                data.resize(0x2000, 0);
-                
-                data.try_into().unwrap()
-            },
+            prg_ram,
+            prg_rom,
+            chr,
+            chr_is_ram,
+            mirroring,
         })
     }
-    
+
     fn read_cpu(&mut self, addr: u16) -> u8 {
         match addr {
             0x6000..=0x7FFF => self.prg_ram[(addr & 0x1FFF) as usize],
@@ -43,23 +58,38 @@ impl Mapper for Mapper000 {
             _ => panic!("Read attempt to invalid address {:#06X}", addr),
         }
     }
-    
-    fn write_cpu(&mut self, addr: u16, data: u8) {
+
+    fn write_cpu(&mut self, addr: u16, data: u8, _cyc: usize) {
         match addr {
             0x6000..=0x7FFF => self.prg_ram[(addr & 0x1FFF) as usize] = data,
             0x8000..=0xFFFF => (),
             _ => panic!("Read attempt to invalid address {:#06X}", addr),
         }
     }
-    
+
     fn read_ppu(&mut self, addr: u16) -> u8 {
         match addr {
-            0x0000..=0x1FFF => self.chr_rom[addr as usize],
+            0x0000..=0x1FFF => self.chr[addr as usize],
             _ => unimplemented!()
         }
     }
-    
-    fn write_ppu(&mut self, _addr: u16, _data: u8) {
-        // do nothing
+
+    fn write_ppu(&mut self, addr: u16, data: u8) {
+        if !self.chr_is_ram {
+            return;
+        }
+
+        match addr {
+            0x0000..=0x1FFF => self.chr[addr as usize] = data,
+            _ => (),
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_state(&self) -> MapperState {
+        MapperState::Mapper000(self.clone())
     }
 }
\ No newline at end of file
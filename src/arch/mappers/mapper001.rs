@@ -0,0 +1,199 @@
+
+//! iNES 001 (MMC1)
+
+use serde::{Deserialize, Serialize};
+use crate::arch::mappers::{Mapper, MapperState, Mirroring, RomFile};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mapper001 {
+    pub prg_ram: Vec<u8>,
+    pub prg_rom: Vec<u8>,
+    pub chr: Vec<u8>,
+    chr_is_ram: bool,
+
+    /// `$8000` - selects mirroring, PRG bank mode, and CHR bank mode
+    pub control: u8,
+    /// `$A000`
+    pub chr_bank0: u8,
+    /// `$C000`
+    pub chr_bank1: u8,
+    /// `$E000`
+    pub prg_bank: u8,
+
+    /// 5-bit serial shift register, LSB first; `shift_count` holds how many bits are loaded
+    shift: u8,
+    shift_count: u8,
+
+    /// CPU cycle of the last write to `$8000-$FFFF`, so a second write landing on the very next
+    /// cycle (as a read-modify-write instruction targeting this range produces) can be dropped;
+    /// see [`Mapper001::write_cpu`].
+    last_write_cyc: Option<usize>,
+
+    prg_bank_count: usize,
+    chr_bank_count: usize,
+}
+impl Mapper001 {
+    fn prg_mode(&self) -> u8 {
+        (self.control >> 2) & 0x03
+    }
+
+    fn chr_mode(&self) -> u8 {
+        (self.control >> 4) & 0x01
+    }
+
+    fn prg_bank_offset(&self, bank: u8) -> usize {
+        (bank as usize % self.prg_bank_count) * 0x4000
+    }
+
+    fn chr_bank_offset(&self, bank: u8, size: usize) -> usize {
+        let bank_count = self.chr.len() / size;
+        (bank as usize % bank_count.max(1)) * size
+    }
+
+    /// Write `data`'s low bit into the serial shift register, loading it into the register
+    /// addressed by `addr` once the fifth bit has been shifted in.
+    fn shift_write(&mut self, addr: u16, data: u8) {
+        if data & 0x80 != 0 {
+            self.shift = 0;
+            self.shift_count = 0;
+            self.control |= 0x0C;
+
+            return;
+        }
+
+        self.shift |= (data & 0x01) << self.shift_count;
+        self.shift_count += 1;
+
+        if self.shift_count == 5 {
+            match addr {
+                0x8000..=0x9FFF => self.control = self.shift,
+                0xA000..=0xBFFF => self.chr_bank0 = self.shift,
+                0xC000..=0xDFFF => self.chr_bank1 = self.shift,
+                0xE000..=0xFFFF => self.prg_bank = self.shift,
+                _ => unreachable!(),
+            }
+
+            self.shift = 0;
+            self.shift_count = 0;
+        }
+    }
+}
+impl Mapper for Mapper001 {
+    fn new(rom: RomFile) -> Box<dyn Mapper> {
+        let chr_is_ram = rom.chr.is_empty();
+        let chr = if chr_is_ram {
+            vec![0u8; rom.chr_ram_size().max(0x2000)]
+        } else {
+            rom.chr.clone()
+        };
+
+        Box::new(Mapper001 {
+            prg_ram: vec![0u8; rom.prg_ram_size().max(0x2000)],
+            prg_bank_count: rom.prg.len() / 0x4000,
+            chr_bank_count: chr.len() / 0x1000,
+            prg_rom: rom.prg,
+            chr,
+            chr_is_ram,
+
+            control: 0x0C, // PRG mode 3 (fix last bank at $C000) at power-on
+            chr_bank0: 0,
+            chr_bank1: 0,
+            prg_bank: 0,
+
+            shift: 0,
+            shift_count: 0,
+            last_write_cyc: None,
+        })
+    }
+
+    fn read_cpu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram[(addr & 0x1FFF) as usize],
+            0x8000..=0xFFFF => {
+                let last_bank = (self.prg_bank_count - 1) as u8;
+                let prg_bank = self.prg_bank & 0x0F;
+
+                let (bank, offset) = match self.prg_mode() {
+                    0 | 1 => (prg_bank & !0x01, (addr & 0x7FFF) as usize), // 32 KiB mode
+                    2 => if addr < 0xC000 { (0, (addr & 0x3FFF) as usize) } else { (prg_bank, (addr & 0x3FFF) as usize) }, // fix first bank at $8000
+                    3 => if addr < 0xC000 { (prg_bank, (addr & 0x3FFF) as usize) } else { (last_bank, (addr & 0x3FFF) as usize) }, // fix last bank at $C000
+                    _ => unreachable!(),
+                };
+
+                self.prg_rom[self.prg_bank_offset(bank) + offset]
+            },
+            _ => panic!("Read attempt to invalid address {:#06X}", addr),
+        }
+    }
+
+    fn write_cpu(&mut self, addr: u16, data: u8, cyc: usize) {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram[(addr & 0x1FFF) as usize] = data,
+            0x8000..=0xFFFF => {
+                // Real MMC1 silicon only samples its serial port every other cycle, so a second
+                // write landing on the very next cycle (what a read-modify-write instruction like
+                // `INC`/`ASL` produces when it targets this range) is dropped rather than shifted in.
+                let consecutive = self.last_write_cyc == Some(cyc.wrapping_sub(1));
+                self.last_write_cyc = Some(cyc);
+
+                if !consecutive {
+                    self.shift_write(addr, data);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn read_ppu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => {
+                let (bank, size, offset) = if self.chr_mode() == 0 {
+                    (self.chr_bank0 & !0x01, 0x2000, addr as usize) // 8 KiB mode, low bit ignored
+                } else if addr < 0x1000 {
+                    (self.chr_bank0, 0x1000, (addr & 0x0FFF) as usize)
+                } else {
+                    (self.chr_bank1, 0x1000, (addr & 0x0FFF) as usize)
+                };
+
+                self.chr[self.chr_bank_offset(bank, size) + offset]
+            },
+            _ => unimplemented!()
+        }
+    }
+
+    fn write_ppu(&mut self, addr: u16, data: u8) {
+        if !self.chr_is_ram {
+            return;
+        }
+
+        match addr {
+            0x0000..=0x1FFF => {
+                let (bank, size, offset) = if self.chr_mode() == 0 {
+                    (self.chr_bank0 & !0x01, 0x2000, addr as usize)
+                } else if addr < 0x1000 {
+                    (self.chr_bank0, 0x1000, (addr & 0x0FFF) as usize)
+                } else {
+                    (self.chr_bank1, 0x1000, (addr & 0x0FFF) as usize)
+                };
+
+                let index = self.chr_bank_offset(bank, size) + offset;
+                self.chr[index] = data;
+            },
+            _ => (),
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SingleScreenLo,
+            1 => Mirroring::SingleScreenHi,
+            2 => Mirroring::Vertical,
+            3 => Mirroring::Horizontal,
+            _ => unreachable!(),
+        }
+    }
+
+    fn save_state(&self) -> MapperState {
+        MapperState::Mapper001(self.clone())
+    }
+}
@@ -0,0 +1,74 @@
+
+//! iNES 002 (UxROM)
+
+use serde::{Deserialize, Serialize};
+use crate::arch::mappers::{Mapper, MapperState, Mirroring, RomFile};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mapper002 {
+    pub prg_rom: Vec<u8>,
+    pub chr_ram: Vec<u8>,
+
+    /// Index of the 16 KiB bank currently switched in at `$8000-$BFFF`
+    pub prg_bank: usize,
+    prg_bank_count: usize,
+    mirroring: Mirroring,
+}
+impl Mapper for Mapper002 {
+    fn new(rom: RomFile) -> Box<dyn Mapper> {
+        let mirroring = rom.mirroring();
+        let chr_ram = if rom.chr.is_empty() {
+            vec![0u8; rom.chr_ram_size().max(0x2000)]
+        } else {
+            rom.chr.clone()
+        };
+
+        Box::new(Mapper002 {
+            prg_bank_count: rom.prg.len() / 0x4000,
+            prg_rom: rom.prg,
+            chr_ram,
+            prg_bank: 0,
+            mirroring,
+        })
+    }
+
+    fn read_cpu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xBFFF => self.prg_rom[(self.prg_bank * 0x4000) + (addr & 0x3FFF) as usize],
+            0xC000..=0xFFFF => {
+                let last_bank = self.prg_bank_count - 1;
+                self.prg_rom[(last_bank * 0x4000) + (addr & 0x3FFF) as usize]
+            },
+            _ => panic!("Read attempt to invalid address {:#06X}", addr),
+        }
+    }
+
+    fn write_cpu(&mut self, addr: u16, data: u8, _cyc: usize) {
+        match addr {
+            0x8000..=0xFFFF => self.prg_bank = (data as usize & 0x0F) % self.prg_bank_count,
+            _ => (),
+        }
+    }
+
+    fn read_ppu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.chr_ram[addr as usize],
+            _ => unimplemented!()
+        }
+    }
+
+    fn write_ppu(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.chr_ram[addr as usize] = data,
+            _ => (),
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_state(&self) -> MapperState {
+        MapperState::Mapper002(self.clone())
+    }
+}
@@ -0,0 +1,69 @@
+
+//! iNES 003 (CNROM)
+
+use serde::{Deserialize, Serialize};
+use crate::arch::mappers::{Mapper, MapperState, Mirroring, RomFile};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mapper003 {
+    pub prg_rom: Vec<u8>,
+    pub chr_rom: Vec<u8>,
+
+    /// Index of the 8 KiB CHR bank currently switched in
+    pub chr_bank: usize,
+    chr_bank_count: usize,
+    mirroring: Mirroring,
+}
+impl Mapper for Mapper003 {
+    fn new(rom: RomFile) -> Box<dyn Mapper> {
+        let mirroring = rom.mirroring();
+
+        Box::new(Mapper003 {
+            prg_rom: if rom.prg.len() == 0x4000 {
+                let mut data = rom.prg.clone();
+                data.extend_from_slice(&rom.prg);
+
+                data
+            } else {
+                rom.prg
+            },
+            chr_bank_count: rom.chr.len() / 0x2000,
+            chr_rom: rom.chr,
+            chr_bank: 0,
+            mirroring,
+        })
+    }
+
+    fn read_cpu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xFFFF => self.prg_rom[(addr & 0x7FFF) as usize],
+            _ => panic!("Read attempt to invalid address {:#06X}", addr),
+        }
+    }
+
+    fn write_cpu(&mut self, addr: u16, data: u8, _cyc: usize) {
+        match addr {
+            0x8000..=0xFFFF => self.chr_bank = (data as usize & 0x03) % self.chr_bank_count,
+            _ => (),
+        }
+    }
+
+    fn read_ppu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.chr_rom[(self.chr_bank * 0x2000) + addr as usize],
+            _ => unimplemented!()
+        }
+    }
+
+    fn write_ppu(&mut self, _addr: u16, _data: u8) {
+        // CHR-ROM, not writable
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_state(&self) -> MapperState {
+        MapperState::Mapper003(self.clone())
+    }
+}
@@ -0,0 +1,77 @@
+
+//! iNES 007 (AxROM)
+
+use serde::{Deserialize, Serialize};
+use crate::arch::mappers::{Mapper, MapperState, Mirroring, RomFile};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mapper007 {
+    pub prg_rom: Vec<u8>,
+    pub chr_ram: Vec<u8>,
+
+    /// Index of the 32 KiB PRG bank currently switched in at `$8000-$FFFF`
+    pub prg_bank: usize,
+    prg_bank_count: usize,
+
+    /// Selects which CIRAM page is mirrored across both nametables.
+    pub single_screen_page: u8,
+}
+impl Mapper for Mapper007 {
+    fn new(rom: RomFile) -> Box<dyn Mapper> {
+        let chr_ram = if rom.chr.is_empty() {
+            vec![0u8; rom.chr_ram_size().max(0x2000)]
+        } else {
+            rom.chr.clone()
+        };
+
+        Box::new(Mapper007 {
+            prg_bank_count: rom.prg.len() / 0x8000,
+            prg_rom: rom.prg,
+            chr_ram,
+            prg_bank: 0,
+            single_screen_page: 0,
+        })
+    }
+
+    fn read_cpu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xFFFF => self.prg_rom[(self.prg_bank * 0x8000) + (addr & 0x7FFF) as usize],
+            _ => panic!("Read attempt to invalid address {:#06X}", addr),
+        }
+    }
+
+    fn write_cpu(&mut self, addr: u16, data: u8, _cyc: usize) {
+        match addr {
+            0x8000..=0xFFFF => {
+                self.prg_bank = (data as usize & 0x07) % self.prg_bank_count;
+                self.single_screen_page = (data >> 4) & 0x01;
+            },
+            _ => (),
+        }
+    }
+
+    fn read_ppu(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.chr_ram[addr as usize],
+            _ => unimplemented!()
+        }
+    }
+
+    fn write_ppu(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.chr_ram[addr as usize] = data,
+            _ => (),
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.single_screen_page {
+            0 => Mirroring::SingleScreenLo,
+            _ => Mirroring::SingleScreenHi,
+        }
+    }
+
+    fn save_state(&self) -> MapperState {
+        MapperState::Mapper007(self.clone())
+    }
+}
@@ -1,20 +1,88 @@
 use std::fmt::{Display, Formatter};
 use proc_bitfield::bitfield;
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
 use tracing::trace;
+use crate::arch::mappers::NametableSlot;
+use crate::arch::scheduler::EventKind;
 use crate::arch::{Nes, ClockDivider};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// CPU/PPU timing region a console was built for.
+///
+/// Drives [`PixelPos`]'s frame geometry (scanline count, odd-frame skip) and the PPU's
+/// master-clock divider; see [`NesRegion::scanline_count`], [`NesRegion::vblank_scanline`],
+/// [`NesRegion::odd_frame_skip`], and [`NesRegion::ppu_clock_divisor`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NesRegion {
+    Ntsc,
+    Pal,
+    /// Famiclone timing: PAL's 312-scanline frame and master-clock divisor, but the vblank flag
+    /// (and NMI) fires earlier, at scanline 291, and the CPU divides that same master clock by 15
+    /// rather than PAL's 16, giving the same NTSC-like 3:1 PPU:CPU ratio off a PAL-speed clock.
+    Dendy,
+}
+impl Default for NesRegion {
+    fn default() -> Self {
+        NesRegion::Ntsc
+    }
+}
+impl NesRegion {
+    /// Number of scanlines per frame, including the pre-render line.
+    pub fn scanline_count(&self) -> u16 {
+        match self {
+            NesRegion::Ntsc => 262,
+            NesRegion::Pal | NesRegion::Dendy => 312,
+        }
+    }
+
+    /// Scanline on which the vblank flag is set and NMI may fire (at its first dot).
+    pub fn vblank_scanline(&self) -> u16 {
+        match self {
+            NesRegion::Ntsc | NesRegion::Pal => 241,
+            NesRegion::Dendy => 291,
+        }
+    }
+
+    /// Whether the pre-render line's last dot is skipped on odd frames when rendering is enabled.
+    pub fn odd_frame_skip(&self) -> bool {
+        matches!(self, NesRegion::Ntsc)
+    }
+
+    /// Number of master-clock cycles per PPU dot (the limit fed to [`Ppu`]'s [`ClockDivider`]).
+    ///
+    /// NTSC's true PPU:CPU ratio is 3:1; PAL and Dendy share a slower master clock (divisor 5
+    /// here) but Dendy's [`NesRegion::cpu_clock_divisor`] divides it by 15 rather than PAL's 16,
+    /// landing back on that same 3:1 ratio instead of PAL's 3.2:1.
+    pub fn ppu_clock_divisor(&self) -> usize {
+        match self {
+            NesRegion::Ntsc => 4,
+            NesRegion::Pal | NesRegion::Dendy => 5,
+        }
+    }
+
+    /// Number of master-clock cycles per CPU cycle (the limit fed to [`Cpu`](crate::arch::cpu::Cpu)'s
+    /// [`ClockDivider`]).
+    pub fn cpu_clock_divisor(&self) -> usize {
+        match self {
+            NesRegion::Ntsc => 12,
+            NesRegion::Pal => 16,
+            NesRegion::Dendy => 15,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PixelPos {
     pub cycle: u16,
     pub scanline: u16,
     pub is_odd: bool,
+    scanline_count: u16,
+    skip_odd_frame: bool,
 }
 impl Default for PixelPos {
-    fn default() -> Self { Self {
-        cycle: 0,
-        scanline: 261,
-        is_odd: true, //TODO: Check what the initial state should be
-    }}
+    fn default() -> Self {
+        Self::new(NesRegion::Ntsc)
+    }
 }
 impl Display for PixelPos {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -22,17 +90,27 @@ impl Display for PixelPos {
     }
 }
 impl PixelPos {
+    pub fn new(region: NesRegion) -> Self {
+        Self {
+            cycle: 0,
+            scanline: region.scanline_count() - 1,
+            is_odd: true, //TODO: Check what the initial state should be
+            scanline_count: region.scanline_count(),
+            skip_odd_frame: region.odd_frame_skip(),
+        }
+    }
+
     pub fn inc(&mut self) {
         self.cycle += 1;
         if self.cycle == 341 {
             self.cycle = 0;
             self.scanline += 1;
-            
-            if self.scanline == 262 {
+
+            if self.scanline == self.scanline_count {
                 self.scanline = 0;
                 self.is_odd = !self.is_odd;
-                
-                if self.is_odd {
+
+                if self.skip_odd_frame && self.is_odd {
                     self.cycle = 1;
                 }
             }
@@ -42,7 +120,7 @@ impl PixelPos {
 
 
 bitfield! {
-    #[derive(Copy, Clone, PartialEq, Eq)]
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct CtrlReg(pub u8): Debug {
         pub base_nametable_addr: u8 @ 0..=1,
         pub vram_addr_inc: bool @ 2,
@@ -55,7 +133,7 @@ bitfield! {
 }
 
 bitfield! {
-    #[derive(Copy, Clone, PartialEq, Eq)]
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct MaskReg(pub u8): Debug {
         pub greyscale: bool @ 0,
         pub show_background_left: bool @ 1,
@@ -69,9 +147,24 @@ bitfield! {
         pub emphasize_blue: bool @ 7,
     }
 }
+impl MaskReg {
+    /// Effective (red, green, blue) emphasis bits, accounting for PAL/Dendy boards wiring the
+    /// red and green emphasis bits to the opposite channels versus NTSC.
+    ///
+    /// Not yet consumed by `Ppu::draw_pixel` - tint isn't applied to rendered pixels yet.
+    pub fn emphasis(&self, region: NesRegion) -> (bool, bool, bool) {
+        let (red, green) = if region == NesRegion::Ntsc {
+            (self.emphasize_red(), self.emphasize_green())
+        } else {
+            (self.emphasize_green(), self.emphasize_red())
+        };
+
+        (red, green, self.emphasize_blue())
+    }
+}
 
 bitfield! {
-    #[derive(Copy, Clone, PartialEq, Eq)]
+    #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct VramAddr(u16): Debug {
         pub coarse_x: u8 @ 0..=4,
         pub coarse_y: u8 @ 5..=9,
@@ -116,7 +209,7 @@ impl VramAddr {
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ppu {
     ports_latch: u8,
     cycles_since_pwrrst: usize,
@@ -140,20 +233,45 @@ pub struct Ppu {
     shift_attrib: u8,
     shift_lower: u16,
     shift_upper: u16,
-    
-    clock_divider: ClockDivider<4>,
-    
+
+    clock_divider: ClockDivider,
+    region: NesRegion,
+
     pub pos: PixelPos,
     /// aka nmi_occurred
     vblank: bool,
     //nmi_output: bool,
-    
+    sprite_overflow: bool,
+    sprite0_hit: bool,
+
+    /// Primary OAM, 64 sprites of 4 bytes each (Y, tile, attributes, X)
+    #[serde(with = "BigArray")]
+    pub oam: [u8; 256],
+    /// Sprites selected for the scanline currently being evaluated, filled all at once at cycle 65
+    secondary_oam: [u8; 32],
+    secondary_oam_count: u8,
+    /// Whether sprite 0 was one of the sprites copied into `secondary_oam` this evaluation
+    sprite0_in_secondary: bool,
+
+    /// Pattern/attribute/x-position latches for the up-to-8 sprites rendered on the current scanline,
+    /// loaded from `secondary_oam` during cycles 257-320 of the previous scanline
+    sprite_pattern_lo: [u8; 8],
+    sprite_pattern_hi: [u8; 8],
+    sprite_attr: [u8; 8],
+    sprite_x_counter: [u8; 8],
+    sprite_count: u8,
+    /// Whether sprite 0 is among the sprites loaded for the scanline currently being rendered
+    sprite0_on_scanline: bool,
+
+    #[serde(with = "BigArray")]
     pub fb: [u32; 256 * 240],
-    
+
     /// Internal VRAM used for storing two nametables
+    #[serde(with = "BigArray")]
     pub ciram: [u8; 0x800],
     pub palettes: [u8; 0x20],
-    
+
+    #[serde(with = "BigArray")]
     pub pal_values: [u32; 0x40],
 }
 impl Default for Ppu {
@@ -179,11 +297,26 @@ impl Default for Ppu {
         shift_lower: 0, //TODO: Check the initial state; maybe it's 0xFFFF?
         shift_upper: 0, //TODO: Check the initial state; maybe it's 0xFFFF?
         
-        clock_divider: ClockDivider::new(0), //todo: randomize
-        
+        clock_divider: ClockDivider::new(0, NesRegion::Ntsc.ppu_clock_divisor()), //todo: randomize
+        region: NesRegion::Ntsc,
+
         pos: PixelPos::default(),
         vblank: false,
-        
+        sprite_overflow: false,
+        sprite0_hit: false,
+
+        oam: [0u8; 256],
+        secondary_oam: [0xFFu8; 32],
+        secondary_oam_count: 0,
+        sprite0_in_secondary: false,
+
+        sprite_pattern_lo: [0u8; 8],
+        sprite_pattern_hi: [0u8; 8],
+        sprite_attr: [0u8; 8],
+        sprite_x_counter: [0u8; 8],
+        sprite_count: 0,
+        sprite0_on_scanline: false,
+
         fb: [0u32; 256 * 240],
         
         ciram: [0u8; 0x800],
@@ -193,6 +326,17 @@ impl Default for Ppu {
     }}
 }
 impl Ppu {
+    /// Construct a PPU configured for the given timing region, so frame geometry and the
+    /// master-clock divider come out right from power-on (see [`NesRegion`]).
+    pub fn new(region: NesRegion) -> Self {
+        Self {
+            clock_divider: ClockDivider::new(0, region.ppu_clock_divisor()),
+            region,
+            pos: PixelPos::new(region),
+            ..Self::default()
+        }
+    }
+
     #[inline(always)]
     pub fn tick(nes: &mut Nes) {
         if nes.ppu.clock_divider.tick() {
@@ -203,13 +347,16 @@ impl Ppu {
     pub fn cycle(nes: &mut Nes) {
         let cycle = nes.ppu.pos.cycle;
         let line = nes.ppu.pos.scanline;
+        let prerender_line = nes.ppu.region.scanline_count() - 1;
+        let vblank_line = nes.ppu.region.vblank_scanline();
         match line {
-            0..=239 | 261 => {
-                if line == 261 && cycle == 1 {
+            l if l <= 239 || l == prerender_line => {
+                if line == prerender_line && cycle == 1 {
                     nes.ppu.vblank = false;
-                    //TODO: clear sprite overflow and sprite 0 hit bits
+                    nes.ppu.sprite_overflow = false;
+                    nes.ppu.sprite0_hit = false;
                 }
-                if line == 261 && (280..=304).contains(&cycle) && (nes.ppu.mask.show_background() || nes.ppu.mask.show_sprites()) {
+                if line == prerender_line && (280..=304).contains(&cycle) && (nes.ppu.mask.show_background() || nes.ppu.mask.show_sprites()) {
                     nes.ppu.vram_addr.0 = (nes.ppu.vram_addr.0 & !0x7BE0) | (nes.ppu.tmp_vram_addr.0 & !0x7BE0);
                 }
                 
@@ -247,14 +394,25 @@ impl Ppu {
                         }
                     }
                     
+                    if cycle == 65 {
+                        Ppu::evaluate_sprites(nes);
+                    }
+
                     if cycle == 256 {
                         nes.ppu.vram_addr.increment_fine_y();
                     }
-                    
+
                     if cycle == 257 {
                         nes.ppu.vram_addr.0 = (nes.ppu.vram_addr.0 & !0x041F) | (nes.ppu.tmp_vram_addr.0 & !0x041F);
+
+                        nes.ppu.sprite_count = nes.ppu.secondary_oam_count;
+                        nes.ppu.sprite0_on_scanline = nes.ppu.sprite0_in_secondary;
                     }
-                    
+
+                    if (257..=320).contains(&cycle) && (cycle - 257) % 8 == 0 {
+                        Ppu::fetch_sprite_pattern(nes, ((cycle - 257) / 8) as usize);
+                    }
+
                     if (321..=340).contains(&cycle) {
                         //TODO
                     }
@@ -268,7 +426,7 @@ impl Ppu {
                     nes.ppu.shift_upper |= 1;
                 }
             },
-            241 if cycle == 1 => {
+            l if l == vblank_line && cycle == 1 => {
                 nes.ppu.vblank = true;
                 Ppu::update_nmi_output(nes);
             },
@@ -303,19 +461,168 @@ impl Ppu {
         
         let x_shift = (attr_x & 0x10) >> 3;
         let attr = (actual_attrib >> (y_shift + x_shift)) & 0x03;
-        let pal_index = (attr << 2) | (bit_upper << 1) | bit_lower;
-        
-        //TODO: Add sprite selection
-        
+        let bg_opaque = (bit_upper << 1 | bit_lower) != 0;
+        let bg_pal_index = (attr << 2) | (bit_upper << 1) | bit_lower;
+
+        let sprite = if (1..=256).contains(&self.pos.cycle) { self.sprite_output() } else { None };
+
+        let pal_index = match sprite {
+            Some((sprite_pal_index, behind_background, is_sprite0)) => {
+                if is_sprite0 && bg_opaque && self.pos.cycle != 256 {
+                    let x = self.pos.cycle - 1;
+                    if (self.mask.show_background_left() && self.mask.show_sprites_left()) || x >= 8 {
+                        self.sprite0_hit = true;
+                    }
+                }
+
+                if behind_background && bg_opaque {
+                    bg_pal_index
+                } else {
+                    sprite_pal_index
+                }
+            },
+            None => bg_pal_index,
+        };
+
         let color = self.pal_values[pal_index as usize];
         if let Some(pixel) = self.fb.get_mut(((self.pos.scanline as usize * 256) + self.pos.cycle as usize) - 15) {
             *pixel = color;
         }
     }
-    
+
+    /// Advance the up-to-8 loaded sprites' shift registers/x-counters by one pixel, returning the
+    /// highest-priority opaque sprite pixel (as a full palette index, background-priority flag, and
+    /// whether it belongs to sprite 0), if any sprite is outputting a pixel this cycle.
+    fn sprite_output(&mut self) -> Option<(u8, bool, bool)> {
+        let mut output = None;
+
+        for i in 0..(self.sprite_count as usize) {
+            if self.sprite_x_counter[i] > 0 {
+                self.sprite_x_counter[i] -= 1;
+                continue;
+            }
+
+            let attr = self.sprite_attr[i];
+            let flip_h = attr & 0x40 != 0;
+
+            let pixel = if flip_h {
+                let pixel = ((self.sprite_pattern_hi[i] & 0x01) << 1) | (self.sprite_pattern_lo[i] & 0x01);
+                self.sprite_pattern_lo[i] >>= 1;
+                self.sprite_pattern_hi[i] >>= 1;
+                pixel
+            } else {
+                let pixel = ((self.sprite_pattern_hi[i] & 0x80) >> 6) | ((self.sprite_pattern_lo[i] & 0x80) >> 7);
+                self.sprite_pattern_lo[i] <<= 1;
+                self.sprite_pattern_hi[i] <<= 1;
+                pixel
+            };
+
+            if pixel != 0 && output.is_none() {
+                let behind_background = attr & 0x20 != 0;
+                let palette = 0x10 | ((attr & 0x03) << 2) | pixel;
+                output = Some((palette, behind_background, i == 0 && self.sprite0_on_scanline));
+            }
+        }
+
+        output
+    }
+
+    /// Scan primary OAM for sprites present on the next scanline, copying up to 8 into secondary
+    /// OAM, and flag sprite overflow using the same buggy diagonal-scan logic as real hardware.
+    fn evaluate_sprites(nes: &mut Nes) {
+        // Evaluating for the *next* scanline, not the one currently being rendered.
+        let scanline = nes.ppu.pos.scanline + 1;
+        let sprite_height: u16 = if nes.ppu.ctrl.sprite_size() { 16 } else { 8 };
+
+        nes.ppu.secondary_oam = [0xFF; 32];
+        nes.ppu.secondary_oam_count = 0;
+        nes.ppu.sprite0_in_secondary = false;
+
+        let in_range = |y: u8| {
+            let y = y as u16;
+            scanline >= y && scanline < y + sprite_height
+        };
+
+        let mut n = 0usize;
+        let mut count = 0usize;
+        while n < 64 && count < 8 {
+            let y = nes.ppu.oam[n * 4];
+            if in_range(y) {
+                let dst = count * 4;
+                nes.ppu.secondary_oam[dst..(dst + 4)].copy_from_slice(&nes.ppu.oam[(n * 4)..(n * 4 + 4)]);
+                if n == 0 {
+                    nes.ppu.sprite0_in_secondary = true;
+                }
+                count += 1;
+            }
+            n += 1;
+        }
+        nes.ppu.secondary_oam_count = count as u8;
+
+        // Buggy overflow detection: once secondary OAM is full, hardware keeps scanning but fails
+        // to reset the low two bits of its OAM address on a miss, so on each subsequent miss it
+        // drifts diagonally through the remaining sprites' non-Y bytes as if they were Y coordinates.
+        let mut m = 0usize;
+        while n < 64 {
+            let y = nes.ppu.oam[(n * 4) + m];
+            if in_range(y) {
+                nes.ppu.sprite_overflow = true;
+                break;
+            }
+            n += 1;
+            m = (m + 1) & 0x03;
+        }
+    }
+
+    /// Fetch the pattern bytes for the `index`-th sprite in secondary OAM, latching its pattern,
+    /// attribute byte, and x-position counter for rendering on the next scanline.
+    fn fetch_sprite_pattern(nes: &mut Nes, index: usize) {
+        let tall = nes.ppu.ctrl.sprite_size();
+        let sprite_height: u16 = if tall { 16 } else { 8 };
+
+        if index >= nes.ppu.secondary_oam_count as usize {
+            // Unused sprite slot; hardware fetches using the secondary OAM's cleared $FF entry,
+            // which resolves to a fully transparent tile out of range of any real sprite.
+            nes.ppu.sprite_pattern_lo[index] = 0;
+            nes.ppu.sprite_pattern_hi[index] = 0;
+            nes.ppu.sprite_attr[index] = 0;
+            nes.ppu.sprite_x_counter[index] = 0xFF;
+            return;
+        }
+
+        let base = index * 4;
+        let y = nes.ppu.secondary_oam[base] as u16;
+        let tile = nes.ppu.secondary_oam[base + 1];
+        let attr = nes.ppu.secondary_oam[base + 2];
+        let x = nes.ppu.secondary_oam[base + 3];
+
+        // Rendering for the *next* scanline, not the one currently being output.
+        let mut row = (nes.ppu.pos.scanline + 1).wrapping_sub(y).min(sprite_height - 1);
+        if attr & 0x80 != 0 { // vertical flip
+            row = sprite_height - 1 - row;
+        }
+
+        let (pattern_table, tile_index, fine_row) = if tall {
+            (tile & 0x01, (tile & 0xFE) + (row / 8) as u8, row % 8)
+        } else {
+            (nes.ppu.ctrl.sprite_pattern_addr() as u8, tile, row)
+        };
+
+        let addr = ((pattern_table as u16) << 12) | ((tile_index as u16) << 4) | fine_row;
+
+        nes.ppu.sprite_pattern_lo[index] = nes.cart.read_ppu(addr);
+        nes.ppu.sprite_pattern_hi[index] = nes.cart.read_ppu(addr + 8);
+        nes.ppu.sprite_attr[index] = attr;
+        nes.ppu.sprite_x_counter[index] = x;
+    }
+
+    /// Schedules an [`EventKind::Nmi`] for the current cycle rather than setting the CPU's `nmi`
+    /// line directly, so this edge-triggered interrupt goes through
+    /// [`Scheduler`](crate::arch::scheduler::Scheduler) like any other discrete timing event.
     fn update_nmi_output(nes: &mut Nes) {
         if nes.ppu.ctrl.generate_nmi() && nes.ppu.vblank {
-            nes.cpu.nmi = false; // set LOW (NMI is active-low)
+            let cyc = nes.cpu.cyc;
+            nes.scheduler.schedule(cyc, EventKind::Nmi);
         }
     }
     
@@ -323,9 +630,7 @@ impl Ppu {
     fn read(nes: &mut Nes, addr: u16) -> u8 {
         match addr & 0x3FFF { // address bus is only 14 bits wide
             0x0000..=0x1FFF => nes.cart.read_ppu(addr),
-            0x2000..=0x3EFF => {
-                nes.ppu.ciram[(addr & 0x7FF) as usize] //TODO: Implement nametable mirroring
-            }
+            0x2000..=0x3EFF => Ppu::read_nametable(nes, addr),
             0x3F00..=0x3FFF => {
                 let addr = (addr & 0x1F) as usize;
                 
@@ -344,6 +649,30 @@ impl Ppu {
         }
     }
     
+    /// Resolve a nametable access in `$2000-$3EFF` to a physical nametable slot, per the
+    /// cartridge's current mirroring, and read from it.
+    fn read_nametable(nes: &mut Nes, addr: u16) -> u8 {
+        let table = (addr >> 10) & 0x03;
+        let offset = addr & 0x03FF;
+
+        match nes.cart.mirroring().resolve(table) {
+            NametableSlot::Ciram(bank) => nes.ppu.ciram[(((bank as u16) << 10) | offset) as usize],
+            NametableSlot::CartVram(bank) => nes.cart.read_nametable(((bank as u16) << 10) | offset),
+        }
+    }
+
+    /// Resolve a nametable access in `$2000-$3EFF` to a physical nametable slot, per the
+    /// cartridge's current mirroring, and write to it.
+    fn write_nametable(nes: &mut Nes, addr: u16, data: u8) {
+        let table = (addr >> 10) & 0x03;
+        let offset = addr & 0x03FF;
+
+        match nes.cart.mirroring().resolve(table) {
+            NametableSlot::Ciram(bank) => nes.ppu.ciram[(((bank as u16) << 10) | offset) as usize] = data,
+            NametableSlot::CartVram(bank) => nes.cart.write_nametable(((bank as u16) << 10) | offset, data),
+        }
+    }
+
     /// Write to PPU memory map (may write into the cartridge)
     fn write(nes: &mut Nes, addr: u16, data: u8) {
         match addr & 0x3FFF { // address bus is only 14 bits wide
@@ -353,8 +682,8 @@ impl Ppu {
                 trace!("PPU Pattern write {data:#04X} to {addr:#06X}");
             },
             0x2000..=0x3EFF => {
-                nes.ppu.ciram[(addr & 0x7FF) as usize] = data; //TODO: Implement nametable mirroring
-                
+                Ppu::write_nametable(nes, addr, data);
+
                 trace!("PPU CIRAM write {data:#04X} to {addr:#06X}");
             },
             0x3F00..=0x3FFF => {
@@ -377,12 +706,15 @@ impl Ppu {
             0x2000 => (),
             0x2001 => (),
             0x2002 => {
-                nes.ppu.ports_latch = ((nes.ppu.vblank as u8) << 7) | (nes.ppu.ports_latch & 0b00011111);
+                nes.ppu.ports_latch = ((nes.ppu.vblank as u8) << 7)
+                    | ((nes.ppu.sprite0_hit as u8) << 6)
+                    | ((nes.ppu.sprite_overflow as u8) << 5)
+                    | (nes.ppu.ports_latch & 0b00011111);
                 nes.ppu.vblank = false;
                 nes.ppu.write_toggle = false;
-            }, //TODO: add sprite overflow and sprite 0 hit detection to status register
+            },
             0x2003 => (),
-            0x2004 => unimplemented!("PPU read from {:#06X}", addr),
+            0x2004 => nes.ppu.ports_latch = nes.ppu.oam[nes.ppu.oam_addr as usize],
             0x2005 => (),
             0x2006 => (),
             0x2007 => {
@@ -415,7 +747,8 @@ impl Ppu {
             0x2002 => (),
             0x2003 => nes.ppu.oam_addr = data, //TODO: Add feature flag for 2C02G's OAM corruption
             0x2004 => {
-                unimplemented!("PPU write {:#04X} to {:#06X}", data, addr);
+                nes.ppu.oam[nes.ppu.oam_addr as usize] = data;
+                nes.ppu.oam_addr = nes.ppu.oam_addr.wrapping_add(1);
             },
             0x2005 => {
                 if !nes.ppu.write_toggle { // w = 0
@@ -0,0 +1,79 @@
+//! Cycle-timestamped event scheduler: a binary-heap-backed queue of typed events, meant as the
+//! eventual replacement for [`Nes::tick`](crate::arch::Nes::tick)'s per-cycle polling of every
+//! component's `proc.cycle`-style state machine (CPU instruction steps, the APU frame counter,
+//! interrupt assertion).
+//!
+//! [`Nes::tick`](crate::arch::Nes::tick) drains this once per cycle and currently only acts on
+//! [`EventKind::Nmi`] (see [`Ppu::update_nmi_output`](crate::arch::ppu::Ppu) in `ppu.rs`), which
+//! now schedules the CPU's NMI line through here instead of setting it directly. Migrating every
+//! other subsystem's cycle counter onto this (CPU instruction steps in particular, via
+//! [`EventKind::CpuResume`]) is a much larger rewrite than fits in one change, so the CPU/APU
+//! still mostly advance by one master cycle at a time for now; new timing-sensitive work can
+//! register here instead of growing another ad hoc counter.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// What a scheduled [`Event`] means once its timestamp is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// Resume a CPU instruction's state machine, in place of polling `proc.cycle`.
+    CpuResume,
+    /// Assert the CPU's edge-triggered NMI line.
+    Nmi,
+    /// Assert or deassert the CPU's level-triggered IRQ line.
+    Irq,
+    /// Clock the APU's frame counter sequencer.
+    ApuFrameCounter,
+    /// Step an in-progress DMC sample-buffer DMA.
+    DmcDma,
+}
+
+/// A typed event due at an absolute CPU-cycle timestamp ([`Cpu::cyc`](crate::arch::cpu::Cpu::cyc)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub at: usize,
+    pub kind: EventKind,
+}
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so `BinaryHeap`, normally a max-heap, pops the soonest-due event first.
+        other.at.cmp(&self.at)
+    }
+}
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Min-heap of pending [`Event`]s, keyed by absolute CPU-cycle timestamp.
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    events: BinaryHeap<Event>,
+}
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `kind` to fire once the global cycle counter reaches `at`.
+    pub fn schedule(&mut self, at: usize, kind: EventKind) {
+        self.events.push(Event { at, kind });
+    }
+
+    /// Timestamp of the soonest pending event, if any -- how far the core loop could fast-forward
+    /// the global cycle counter before it would need to stop and dispatch something.
+    pub fn next_at(&self) -> Option<usize> {
+        self.events.peek().map(|event| event.at)
+    }
+
+    /// Pop and return the next event, if its timestamp has already been reached by `now`.
+    pub fn pop_ready(&mut self, now: usize) -> Option<Event> {
+        if self.next_at()? <= now {
+            self.events.pop()
+        } else {
+            None
+        }
+    }
+}